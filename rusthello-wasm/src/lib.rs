@@ -7,9 +7,47 @@ pub fn on_load() {
     log!("rusthello WASM initialized");
 }
 
+use std::time::Duration;
+
 use rusthello::Game;
+use rusthello::Move;
 use rusthello::Player;
-use rusthello::{AlphaBeta, VirtualPlayer};
+use rusthello::{
+    square_to_string, AlphaBeta, BookPlayer, OpeningBook, SearchResult, TimeLimit, VirtualPlayer,
+};
+
+/// The maximum depth when the computer is time limited.
+const MAX_DEPTH: u8 = 60;
+
+/// Positions having at most this count of empty squares are solved exactly.
+/// It's lower than the CLI one, as WASM is slower.
+const ENDGAME_EMPTIES: u8 = 12;
+
+/// Clock based on the JavaScript one, as `std::time` is not available.
+fn js_clock() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+/// Returns the moves as a transcript, ie `f5d6c3`.
+fn transcript(moves: &[(u8, u8)]) -> String {
+    moves.iter().map(|&(x, y)| square_to_string(x, y)).collect()
+}
+
+/// Creates the computer player searching at a fixed depth.
+fn computer(depth: u8) -> Box<dyn VirtualPlayer> {
+    Box::new(
+        AlphaBeta::new(depth)
+            .with_endgame_solver(ENDGAME_EMPTIES)
+            .with_clock(js_clock),
+    )
+}
+
+/// Wraps the computer player to play the moves of an opening book, empty
+/// until one is loaded.
+fn with_book(computer: Box<dyn VirtualPlayer>) -> BookPlayer<Box<dyn VirtualPlayer>> {
+    let seed = (js_sys::Math::random() * u64::MAX as f64) as u64;
+    BookPlayer::new(OpeningBook::new(), computer).with_seed(seed)
+}
 
 #[wasm_bindgen]
 pub enum WPlayer {
@@ -26,9 +64,9 @@ impl From<Player> for WPlayer {
     }
 }
 
-impl Into<Player> for WPlayer {
-    fn into(self) -> Player {
-        match self {
+impl From<WPlayer> for Player {
+    fn from(player: WPlayer) -> Self {
+        match player {
             WPlayer::Black => Player::Black,
             WPlayer::White => Player::White,
         }
@@ -37,10 +75,7 @@ impl Into<Player> for WPlayer {
 
 impl WPlayer {
     fn from_option_player(p: Option<Player>) -> Option<WPlayer> {
-        match p {
-            Some(p) => Some(WPlayer::from(p)),
-            None => None,
-        }
+        p.map(WPlayer::from)
     }
 }
 
@@ -50,11 +85,36 @@ pub struct Coordinates {
     pub y: u8,
 }
 
+/// A move of the human player, evaluated by the computer.
+#[wasm_bindgen]
+pub struct ScoredMove {
+    pub x: u8,
+    pub y: u8,
+    /// The evaluation of the move from the human point of view (greater is
+    /// better). Unless `exact` is set, it's a heuristic evaluation, won games
+    /// being evaluated to `i32::MAX`.
+    pub score: i32,
+    /// Whether the position was solved, the score being then the final disc
+    /// differential with a perfect play.
+    pub exact: bool,
+    expected_moves: String,
+}
+
+#[wasm_bindgen]
+impl ScoredMove {
+    /// Returns the expected moves, starting with this one, ie `f5d6c3`.
+    #[wasm_bindgen(getter)]
+    pub fn expected_moves(&self) -> String {
+        self.expected_moves.clone()
+    }
+}
+
 #[wasm_bindgen]
 pub struct WGame {
     game: Game,
     human: Player,
-    terminator: Box<dyn VirtualPlayer>,
+    terminator: BookPlayer<Box<dyn VirtualPlayer>>,
+    last_search: Option<SearchResult>,
 }
 
 #[wasm_bindgen]
@@ -63,10 +123,67 @@ impl WGame {
         Self {
             game: Game::new(),
             human: p.into(),
-            terminator: Box::new(AlphaBeta::new(depth)),
+            terminator: with_book(computer(depth)),
+            last_search: None,
         }
     }
 
+    /// Creates a game where the computer thinks about each move during the
+    /// given time (in milliseconds), instead of searching at a fixed depth.
+    pub fn new_with_time_limit(p: WPlayer, milliseconds: u32) -> Self {
+        let time_limit =
+            TimeLimit::with_clock(Duration::from_millis(milliseconds as u64), js_clock);
+        Self {
+            game: Game::new(),
+            human: p.into(),
+            terminator: with_book(Box::new(
+                AlphaBeta::new(MAX_DEPTH)
+                    .with_time_limit(time_limit)
+                    .with_endgame_solver(ENDGAME_EMPTIES),
+            )),
+            last_search: None,
+        }
+    }
+
+    /// Creates a game from a transcript of moves, ie `f5d6c3d3c4`.
+    pub fn from_transcript(p: WPlayer, depth: u8, transcript: &str) -> Result<WGame, JsValue> {
+        Ok(Self {
+            game: Game::from_transcript(transcript)?,
+            human: p.into(),
+            terminator: with_book(computer(depth)),
+            last_search: None,
+        })
+    }
+
+    /// Returns the transcript of the moves played, ie `f5d6c3d3c4`. It fails
+    /// for a game created from a position.
+    pub fn transcript(&self) -> Result<String, JsValue> {
+        Ok(self.game.to_transcript()?)
+    }
+
+    /// Creates a game starting from a position : the 64 cells from A1 to H8
+    /// (`X`, `O` or `-`) and the next player (`X` or `O`).
+    pub fn from_position(p: WPlayer, depth: u8, position: &str) -> Result<WGame, JsValue> {
+        Ok(Self {
+            game: Game::from_position(position)?,
+            human: p.into(),
+            terminator: with_book(computer(depth)),
+            last_search: None,
+        })
+    }
+
+    /// Loads an opening book, one opening per line (ie `f5d6c3d3c4`), whose
+    /// moves are then played by the computer while the game is in the book.
+    pub fn load_opening_book(&mut self, book: &str) -> Result<(), JsValue> {
+        *self.terminator.book_mut() = OpeningBook::from_text(book)?;
+        Ok(())
+    }
+
+    /// Returns the current position, see `from_position`.
+    pub fn position(&self) -> String {
+        self.game.to_position()
+    }
+
     pub fn player(&self) -> Option<WPlayer> {
         WPlayer::from_option_player(self.game.player())
     }
@@ -109,9 +226,11 @@ impl WGame {
             _ => {} // it's ok
         }
 
-        let computer_move = self
+        let result = self
             .terminator
-            .compute_move(self.game.board(), self.human.opponent());
+            .search(self.game.board(), self.human.opponent());
+        let computer_move = result.best_move;
+        self.last_search = Some(result);
 
         let (x, y) = match computer_move {
             Some((x, y)) => (x, y),
@@ -124,6 +243,82 @@ impl WGame {
         }
     }
 
+    /// Returns the evaluation of the last computer move, from its point of
+    /// view (greater is better), ie to show an evaluation bar.
+    /// Unless `computer_score_is_exact`, it's a heuristic evaluation, won
+    /// games being evaluated to `i32::MAX`.
+    pub fn computer_score(&self) -> Option<i32> {
+        self.last_search.as_ref().map(|result| result.score)
+    }
+
+    /// Returns whether the position was solved when the computer played its
+    /// last move, `computer_score` being then the final disc differential.
+    pub fn computer_score_is_exact(&self) -> bool {
+        self.last_search.as_ref().is_some_and(|result| result.exact)
+    }
+
+    /// Returns the moves the computer expected when it played its last move,
+    /// starting with it, ie `f5d6c3`.
+    pub fn computer_expected_moves(&self) -> String {
+        self.last_search
+            .as_ref()
+            .map(|result| transcript(&result.principal_variation))
+            .unwrap_or_default()
+    }
+
+    /// Evaluates each move of the human player with a search of the given
+    /// depth, and returns them as `ScoredMove`, the best first.
+    pub fn hints(&self, depth: u8) -> Result<js_sys::Array, JsValue> {
+        if self.game.player() != Some(self.human) {
+            return Err(JsValue::from_str("It's not the turn of the human player."));
+        }
+
+        let hints = computer(depth)
+            .analyze(self.game.board(), self.human)
+            .into_iter()
+            .filter_map(|result| {
+                let (x, y) = result.best_move?;
+                Some(JsValue::from(ScoredMove {
+                    x,
+                    y,
+                    score: result.score,
+                    exact: result.exact,
+                    expected_moves: transcript(&result.principal_variation),
+                }))
+            })
+            .collect();
+        Ok(hints)
+    }
+
+    /// Returns whether the human player has a move to take back.
+    pub fn can_undo(&self) -> bool {
+        self.game
+            .history()
+            .iter()
+            .any(|m| matches!(m, Move::Play { player, .. } if *player == self.human))
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.game.can_redo()
+    }
+
+    /// Takes back moves until it's the turn of the human player again.
+    pub fn undo(&mut self) -> Result<(), JsValue> {
+        self.game.undo_for(self.human)?;
+        self.last_search = None;
+        Ok(())
+    }
+
+    /// Plays again taken back moves until it's the turn of the human player
+    /// again.
+    pub fn redo(&mut self) -> Result<(), JsValue> {
+        self.game.redo()?;
+        while self.game.player() != Some(self.human) && self.game.can_redo() {
+            self.game.redo()?;
+        }
+        Ok(())
+    }
+
     pub fn get_piece(&self, x: u8, y: u8) -> Result<Option<WPlayer>, JsValue> {
         let piece = self.game.board().get_piece(x, y)?;
         Ok(WPlayer::from_option_player(piece))
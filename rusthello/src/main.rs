@@ -1,77 +1,450 @@
-use rusthello::{board_to_ascii, AlphaBeta, Game, Player, VirtualPlayer};
+mod options;
+
+use options::{EngineOptions, Options, ENGINE_OPTIONS, GAME_OPTIONS};
+use rusthello::{
+    board_to_ascii, parse_square, square_to_string, standings_table, EndgameSolver, Game,
+    GtpEngine, NBoardEngine, Player, SearchResult, Tournament, VirtualPlayer,
+};
 use std::{
-    char, env,
+    char, env, fs,
     io::{self, Write},
-    process,
+    process, thread,
+    time::{Duration, Instant},
 };
 
+/// The depth of the computer when none is given.
+const DEFAULT_DEPTH: u8 = 6;
+
+/// The maximum depth of the NBoard engine, deeper searches being too slow.
+const NBOARD_MAX_DEPTH: u8 = 10;
+
+/// The openings of tournaments : the 3 different second moves.
+const TOURNAMENT_OPENINGS: [&str; 3] = ["f5d6", "f5f6", "f5f4"];
+
+const USAGE: &str = "\
+Usage : rusthello command [options]
+
+Commands :
+  play [--black seat] [--white seat] [--delay seconds] [game options]
+       [engine options]
+      plays a game, a seat being 'human', 'computer' (the virtual player of
+      the engine options) or a level (see below), the human playing black
+      against the computer by default ; the computer waits for the delay
+      after each of its moves (0 by default)
+  play [--color black|white] [game options] [engine options]
+      plays against the computer with the given color
+  analyze [game options] [engine options]
+      evaluates each move of the next player, the best first
+  selfplay [game options] [engine options]
+      lets the computer play against itself up to the end of the game
+  perft depth [game options]
+      counts the positions reached after 1 .. depth plies, passes included
+  solve [game options]
+      finds the perfect play and the final score (slow with many empty squares)
+  engine [--protocol nboard|gtp] [engine options]
+      speaks the NBoard protocol (the default) or a GTP dialect over stdin
+      and stdout, see the README
+  tournament level level... [--openings file] [engine options]
+      plays each level against each other one from each opening, with both
+      colors, the openings being transcripts, one per line
+  help
+      displays this message
+
+Game options :
+  --position position : the starting position, 64 cells and the next player
+      ie '---------------------------OX------XO--------------------------- X'
+  --moves transcript : the moves from the start position, ie 'f5d6c3'
+
+Engine options :
+  --engine alphabeta|parallel|pvs|minimax|mcts : the search algorithm,
+      parallel (alpha-beta using all the cores) by default, or alphabeta
+      with a time limit
+  --depth depth : the depth of the search, 1 .. 60, 6 by default
+  --time seconds : the time limit per move, only supported by alphabeta and
+      mcts
+  --playouts count : the count of random games per move (mcts), 5000 by
+      default
+  --evaluator simple|classic : the evaluation of positions, simple by default
+  --threads count : the count of threads (parallel), the cores by default
+  --book file : an opening book, ie 'books/openings.txt'
+  --seed seed : the seed of the random choices (mcts and book)
+
+Levels :
+  a depth (ie '6'), a time limit in seconds (ie '5s') or a count of random
+  games (ie '5000p'), optionally prefixed by an engine (ie 'pvs:8'), or only an
+  engine (ie 'mcts'), the engine options giving the rest";
+
+/// Who plays for a player.
+enum Seat {
+    Human,
+    Computer(Box<dyn VirtualPlayer>),
+}
+
+/// The virtual player of the engine options giving hints and evaluations to
+/// the human players, only built when they ask for them.
+struct Analyst {
+    engine_options: EngineOptions,
+    player: Option<Box<dyn VirtualPlayer>>,
+}
+
+impl Analyst {
+    fn new(engine_options: EngineOptions) -> Self {
+        Self {
+            engine_options,
+            player: None,
+        }
+    }
+
+    /// Returns the virtual player, building it on the first call.
+    fn player(&mut self) -> &dyn VirtualPlayer {
+        let engine_options = &self.engine_options;
+        &**self
+            .player
+            .get_or_insert_with(|| engine_options.clone().build())
+    }
+}
+
+/// The commands of a human player.
+#[derive(Debug, PartialEq)]
 enum Choice {
-    Quit,
     Move { x: u8, y: u8 },
+    Undo,
+    Hint,
+    Eval,
+    Moves,
+    Save(String),
+    Load(String),
+    Flip,
+    Help,
+    Quit,
 }
 
+/// How the game goes on after a command of a human player.
+enum Turn {
+    Continue,
+    Flip,
+    Quit,
+}
+
+/// The help of the commands of a human player.
+const COMMANDS: &str = "\
+Commands :
+  a square (ie F5) : plays there
+  undo : takes back your last move, and the reply of the computer
+  hint (or h) : evaluates each of your moves, the best first
+  eval : evaluates the position
+  moves : lists your moves
+  save file : saves the game, in the GGF format
+  load file : loads a game saved by save
+  flip : swaps the sides, the computer playing your color
+  help : displays this message
+  quit (or q) : leaves the game";
+
 fn main() {
-    let (human, depth) = parge_args();
-    let computer: &dyn VirtualPlayer = &AlphaBeta::new(depth);
-
-    let mut game = Game::new();
-    while !game.game_over() {
-        if game.player().unwrap() == human {
-            let mut valid_move = false;
-            while !valid_move {
-                match get_choice_from_player(&game) {
-                    Choice::Quit => return,
-                    Choice::Move { x, y } => {
-                        if let Ok(_) = game.play(game.player().unwrap(), x, y) {
-                            valid_move = true
-                        }
-                    }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => ("help", &args[..]),
+    };
+    let result = Options::parse(args).and_then(|options| match command {
+        "play" => play(&options),
+        "analyze" => analyze(&options),
+        "selfplay" => selfplay(&options),
+        "perft" => perft(&options),
+        "solve" => solve(&options),
+        "engine" => engine(&options),
+        "tournament" => tournament(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command '{}'.", command)),
+    });
+
+    if let Err(message) = result {
+        println!("{}", message);
+        println!();
+        println!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+/// Plays a game, each seat being taken by a human or a virtual player.
+fn play(options: &Options) -> Result<(), String> {
+    let mut names = vec!["color", "black", "white", "delay"];
+    names.extend_from_slice(&GAME_OPTIONS);
+    names.extend_from_slice(&ENGINE_OPTIONS);
+    options.check(&names, 0)?;
+    let (black, white) = match (
+        options.get("color"),
+        options.get("black"),
+        options.get("white"),
+    ) {
+        (None, black, white) => (black.unwrap_or("human"), white.unwrap_or("computer")),
+        (Some("black"), None, None) => ("human", "computer"),
+        (Some("white"), None, None) => ("computer", "human"),
+        (Some(_), None, None) => return Err("The color must be black or white.".to_string()),
+        (Some(_), _, _) => return Err("Either --color or the seats can be given.".to_string()),
+    };
+    // Read once, as the opening book is loaded with them.
+    let engine_options = EngineOptions::from_options(options, DEFAULT_DEPTH)?;
+    let mut seats = [
+        seat(options, &engine_options, black)?,
+        seat(options, &engine_options, white)?,
+    ];
+    let mut analyst = Analyst::new(engine_options);
+    let delay = options
+        .get_number::<f64>("delay", (0.0, 60.0))?
+        .map_or(Duration::from_secs(0), Duration::from_secs_f64);
+    let mut game = options.game()?;
+
+    while let Some(player) = game.player() {
+        match &seats[player as usize] {
+            Seat::Human => {
+                let opponent_is_human = matches!(seats[player.opponent() as usize], Seat::Human);
+                match human_turn(&mut game, player, opponent_is_human, &mut analyst) {
+                    Turn::Continue => (),
+                    Turn::Flip => seats.swap(0, 1),
+                    Turn::Quit => return Ok(()),
                 }
             }
-        } else {
-            display_game_status(&game);
-            println!("Computer is thinking...");
-            let (x, y) = computer
-                .compute_move(&game.board(), human.opponent())
-                .expect("The computer can't produce a move.");
-            game.play(human.opponent(), x, y).unwrap();
-            println!("Computer played at {}", readable_coordinates(x, y));
+            Seat::Computer(computer) => {
+                display_game_status(&game);
+                println!("{} is thinking...", player);
+                let result = computer.search(game.board(), player);
+                let (x, y) = result
+                    .best_move
+                    .expect("The computer can't produce a move.");
+                game.play(player, x, y).unwrap();
+                if result.from_book {
+                    println!(
+                        "{} played at {} from its opening book",
+                        player,
+                        readable_coordinates(x, y)
+                    );
+                } else {
+                    println!(
+                        "{} played at {} (depth {}, {} positions in {:.1}s)",
+                        player,
+                        readable_coordinates(x, y),
+                        result.depth,
+                        result.nodes,
+                        result.elapsed.as_secs_f64()
+                    );
+                    println!("{}", describe_expectation(player, &result));
+                }
+                thread::sleep(delay);
+            }
         }
     }
     display_game_status(&game);
+
+    Ok(())
 }
 
-fn parge_args() -> (Player, u8) {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 3 {
-        print_usage_and_exit();
+/// Returns the seat described by `human`, `computer` (the virtual player of
+/// the engine options) or a level, see `EngineOptions::from_level`.
+fn seat(
+    options: &Options,
+    engine_options: &EngineOptions,
+    description: &str,
+) -> Result<Seat, String> {
+    let engine_options = match description {
+        "human" => return Ok(Seat::Human),
+        "computer" => engine_options.clone(),
+        level => EngineOptions::from_level(options, level, DEFAULT_DEPTH)?,
+    };
+
+    Ok(Seat::Computer(engine_options.build()))
+}
+
+/// Displays the evaluation of each move of the next player, the best first.
+fn analyze(options: &Options) -> Result<(), String> {
+    let mut names = GAME_OPTIONS.to_vec();
+    names.extend_from_slice(&ENGINE_OPTIONS);
+    options.check(&names, 0)?;
+    let game = options.game()?;
+    let player = game
+        .player()
+        .ok_or_else(|| "The game is over, there is nothing to analyze.".to_string())?;
+    let analyst = EngineOptions::from_options(options, DEFAULT_DEPTH)?.build();
+
+    println!("{}", board_to_ascii(game.board()));
+    println!("Moves of {} :", player);
+    display_analysis(&game, analyst.as_ref());
+
+    Ok(())
+}
+
+/// Lets the computer play against itself, displaying each move.
+fn selfplay(options: &Options) -> Result<(), String> {
+    let mut names = GAME_OPTIONS.to_vec();
+    names.extend_from_slice(&ENGINE_OPTIONS);
+    options.check(&names, 0)?;
+    let mut game = options.game()?;
+    let computer = EngineOptions::from_options(options, DEFAULT_DEPTH)?.build();
+
+    while let Some(player) = game.player() {
+        let result = computer.search(game.board(), player);
+        let (x, y) = result
+            .best_move
+            .ok_or_else(|| format!("The computer can't find a move for {}.", player))?;
+        game.play(player, x, y)?;
+        println!(
+            "{:>2}. {} {} (depth {}, {} in {:.1}s)",
+            game.ply(),
+            player,
+            square_to_string(x, y).to_uppercase(),
+            result.depth,
+            describe_score(&result),
+            result.elapsed.as_secs_f64()
+        );
+    }
+    display_game_status(&game);
+    // A game started from a given position has no transcript.
+    match game.to_transcript() {
+        Ok(transcript) => println!("{}", transcript),
+        Err(_) => println!("{}", game.to_ggf()),
     }
 
-    let player_str = args[1].trim().to_ascii_lowercase();
-    let player = match player_str.as_str() {
-        "black" => Player::Black,
-        "white" => Player::White,
-        _ => print_usage_and_exit(),
+    Ok(())
+}
+
+/// Displays the count of positions reached from the game after each count
+/// of plies, up to the given depth.
+fn perft(options: &Options) -> Result<(), String> {
+    options.check(&GAME_OPTIONS, 1)?;
+    let depth = match options
+        .positional()
+        .first()
+        .map(|depth| depth.parse::<u8>())
+    {
+        Some(Ok(depth)) if (1..=20).contains(&depth) => depth,
+        _ => return Err("The depth of perft must be a number from 1 to 20.".to_string()),
     };
+    let game = options.game()?;
 
-    match args[2].parse::<u8>() {
-        Ok(depth) => {
-            if depth < 4 || depth > 10 {
-                print_usage_and_exit();
-            }
-            return (player, depth);
+    for depth in 1..=depth {
+        let start = Instant::now();
+        let count = game.perft(depth);
+        println!(
+            "perft({}) = {} ({:.1}s)",
+            depth,
+            count,
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
+
+/// Displays the perfect play from the game and the final score.
+fn solve(options: &Options) -> Result<(), String> {
+    options.check(&GAME_OPTIONS, 0)?;
+    let game = options.game()?;
+    let player = game
+        .player()
+        .ok_or_else(|| "The game is over, there is nothing to solve.".to_string())?;
+
+    let start = Instant::now();
+    let solution = EndgameSolver::new().solve(game.board(), player);
+    println!(
+        "{} {} by {} after {} ({:.1}s)",
+        player,
+        match solution.score {
+            score if score > 0 => "wins",
+            score if score < 0 => "loses",
+            _ => "draws",
+        },
+        solution.score.abs(),
+        describe_variation(&solution.principal_variation),
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
+
+/// Runs an engine speaking the NBoard protocol or a GTP dialect over stdin
+/// and stdout. The depths set by NBoard are limited to `NBOARD_MAX_DEPTH`,
+/// and ignored with a time limit.
+fn engine(options: &Options) -> Result<(), String> {
+    let mut names = vec!["protocol"];
+    names.extend_from_slice(&ENGINE_OPTIONS);
+    options.check(&names, 0)?;
+    let engine_options = EngineOptions::from_options(options, DEFAULT_DEPTH)?;
+
+    let result = match options.get("protocol") {
+        None | Some("nboard") => {
+            let depth = engine_options.depth;
+            let mut engine = NBoardEngine::new(depth, move |depth| {
+                let mut engine_options = engine_options.clone();
+                if engine_options.time.is_none() {
+                    engine_options.depth = depth.min(NBOARD_MAX_DEPTH);
+                }
+                engine_options.build()
+            });
+            engine.run(io::stdin().lock(), io::stdout().lock())
         }
-        Err(_) => {
-            print_usage_and_exit();
+        Some("gtp") => {
+            let mut engine = GtpEngine::new(engine_options.build());
+            engine.run(io::stdin().lock(), io::stdout().lock())
         }
+        Some(other) => return Err(format!("Unknown protocol '{}'.", other)),
     };
+
+    result.map_err(|error| error.to_string())
 }
 
-fn print_usage_and_exit() -> ! {
-    println!("Usage : {} color depth", env::args().nth(0).unwrap());
-    println!("  color : 'black' or 'white'");
-    println!("  depth : 4 .. 10 (more than 8 could be slow)");
-    process::exit(1);
+/// Runs a tournament between the given levels, displaying the result of
+/// each game and the standings.
+fn tournament(options: &Options) -> Result<(), String> {
+    let mut names = vec!["openings"];
+    names.extend_from_slice(&ENGINE_OPTIONS);
+    options.check(&names, usize::MAX)?;
+    let levels = options.positional();
+    if levels.len() < 2 {
+        return Err("A tournament needs at least 2 levels.".to_string());
+    }
+    let openings_file = match options.get("openings") {
+        None => None,
+        Some(path) => Some(
+            fs::read_to_string(path)
+                .map_err(|error| format!("Unable to read {} : {}", path, error))?,
+        ),
+    };
+    let openings: Vec<&str> = match &openings_file {
+        None => TOURNAMENT_OPENINGS.to_vec(),
+        Some(text) => text
+            .lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .filter(|line| !line.is_empty())
+            .collect(),
+    };
+
+    let mut tournament = Tournament::new(&openings)?;
+    for level in levels {
+        let player = EngineOptions::from_level(options, level, DEFAULT_DEPTH)?.build();
+        tournament = tournament.with_player(level, player);
+    }
+    let game_count = tournament.game_count();
+    let mut played = 0;
+    let standings = tournament.run(|record| {
+        played += 1;
+        println!(
+            "Game {}/{} from {} : {} {} - {} {}",
+            played,
+            game_count,
+            openings[record.opening],
+            levels[record.black],
+            record.black_discs,
+            record.white_discs,
+            levels[record.white]
+        );
+    })?;
+    println!();
+    print!("{}", standings_table(&standings));
+
+    Ok(())
 }
 
 fn display_game_status(game: &Game) {
@@ -91,78 +464,223 @@ fn display_game_status(game: &Game) {
     }
 
     let player = game.player().expect("Unexpected None player");
-    if game.opponent_is_blocked() {
-        println!(
-            "The turn does not change as {} can't move.",
-            player.opponent()
-        );
+    if let Some(passing_player) = game.last_pass() {
+        println!("{} can't move and passes.", passing_player);
     }
 
     println!("It's the turn of {}.", player);
 }
 
-fn readable_coordinates(x: u8, y: u8) -> String {
-    let letter = char::from_u32('A' as u32 + x as u32).unwrap();
-    let digit = y + 1;
+/// Describes the score and the principal variation of a search, ie
+/// `White expects +6 after F5 D6 C3`, or `White expects to win by 6 discs
+/// after F5 D6 C3` once the position is solved.
+fn describe_expectation(player: Player, result: &SearchResult) -> String {
+    let score = match (result.exact, result.score) {
+        (_, i32::MAX) => "to win".to_string(),
+        (_, score) if score == -i32::MAX => "to lose".to_string(),
+        (true, score) if score > 0 => format!("to win by {} discs", score),
+        (true, score) if score < 0 => format!("to lose by {} discs", -score),
+        (true, _) => "a draw".to_string(),
+        (false, score) => format!("{:+}", score),
+    };
 
-    format!("({}, {})", letter, digit)
+    format!(
+        "{} expects {} after {}",
+        player,
+        score,
+        describe_variation(&result.principal_variation)
+    )
 }
 
-fn get_choice_from_player(game: &Game) -> Choice {
-    let mut choice: Option<Choice> = None;
-    let mut bad_response = false;
-    while choice.is_none() || bad_response {
-        display_game_status(&game);
-        if bad_response {
-            println!("Previous response was invalid, let try again.")
-        }
-        choice = read_choice();
-        bad_response = choice.is_none();
+/// Describes the score of a search, ie `+6`, `win` or `loss`, or `+6 discs`
+/// once the position is solved.
+fn describe_score(result: &SearchResult) -> String {
+    match (result.exact, result.score) {
+        (_, i32::MAX) => "win".to_string(),
+        (_, score) if score == -i32::MAX => "loss".to_string(),
+        (true, score) => format!("{:+} discs", score),
+        (false, score) => format!("{:+}", score),
     }
-
-    choice.unwrap()
 }
 
-fn read_choice() -> Option<Choice> {
-    println!("What's you're move ? (ex : A1 ou Q to quit)");
-    print!("> ");
-    io::stdout().flush().unwrap();
-    let response = read_string();
+/// Describes a sequence of moves, ie `F5 D6 C3`.
+fn describe_variation(variation: &[(u8, u8)]) -> String {
+    let moves: Vec<String> = variation
+        .iter()
+        .map(|&(x, y)| square_to_string(x, y).to_uppercase())
+        .collect();
+    moves.join(" ")
+}
 
-    parse_response(response)
+/// Displays the moves of the current player, the best first.
+fn display_hints(game: &Game, analyst: &dyn VirtualPlayer) {
+    let player = game.player().expect("Unexpected None player");
+    println!("Analyzing the moves of {}...", player);
+    display_analysis(game, analyst);
 }
 
-fn parse_response(s: String) -> Option<Choice> {
-    let s = s.to_uppercase();
-    if s == "Q" {
-        return Some(Choice::Quit);
+/// Displays the score and the expected continuation of each move of the
+/// current player, the best first.
+fn display_analysis(game: &Game, analyst: &dyn VirtualPlayer) {
+    let player = game.player().expect("Unexpected None player");
+    for result in analyst.analyze(game.board(), player) {
+        println!(
+            "  {:>9} : {}",
+            describe_score(&result),
+            describe_variation(&result.principal_variation)
+        );
     }
+}
 
-    if s.len() != 2 {
-        return None;
-    }
-    let mut s_chars = s.chars();
-    let x = s_chars.next().unwrap() as i8 - 65; // 'A' = 65
-    let y = s_chars.next().unwrap() as i8 - 49; // '1' = 49
+/// Displays the evaluation of the position for the current player.
+fn display_evaluation(game: &Game, analyst: &dyn VirtualPlayer) {
+    let player = game.player().expect("Unexpected None player");
+    let result = analyst.search(game.board(), player);
+    println!("{}", describe_expectation(player, &result));
+}
 
-    if x < 0 || x > 7 || y < 0 || y > 7 {
-        return None;
+/// Displays the moves of the current player.
+fn display_moves(game: &Game) {
+    let player = game.player().expect("Unexpected None player");
+    println!(
+        "Moves of {} : {}",
+        player,
+        describe_variation(&game.legal_moves())
+    );
+}
+
+fn readable_coordinates(x: u8, y: u8) -> String {
+    let letter = char::from_u32('A' as u32 + x as u32).unwrap();
+    let digit = y + 1;
+
+    format!("({}, {})", letter, digit)
+}
+
+/// Reads and executes the commands of a human player until a move is
+/// played, or the game changes. Hints and evaluations are given by the
+/// analyst. The end of the input quits the game.
+fn human_turn(
+    game: &mut Game,
+    player: Player,
+    opponent_is_human: bool,
+    analyst: &mut Analyst,
+) -> Turn {
+    display_game_status(game);
+    loop {
+        println!("Your move, {} ? (ie F5, or help for the commands)", player);
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let choice = match read_string().as_deref().map(parse_choice) {
+            None => {
+                println!();
+                Choice::Quit
+            }
+            Some(Ok(choice)) => choice,
+            Some(Err(message)) => {
+                println!("{}", message);
+                continue;
+            }
+        };
+
+        let result = match choice {
+            Choice::Move { x, y } => game.play(player, x, y).map(|_| Turn::Continue),
+            Choice::Undo if opponent_is_human => game.undo().map(|_| Turn::Continue),
+            Choice::Undo => game.undo_for(player).map(|_| Turn::Continue),
+            Choice::Load(file) => fs::read_to_string(&file)
+                .map_err(|error| format!("Unable to read {} : {}.", file, error))
+                .and_then(|ggf| Game::from_ggf(&ggf))
+                .map(|loaded| {
+                    *game = loaded;
+                    Turn::Continue
+                }),
+            Choice::Flip => Ok(Turn::Flip),
+            Choice::Quit => Ok(Turn::Quit),
+            Choice::Save(file) => {
+                match fs::write(&file, game.to_ggf()) {
+                    Ok(()) => println!("The game is saved in {}.", file),
+                    Err(error) => println!("Unable to write {} : {}.", file, error),
+                }
+                continue;
+            }
+            Choice::Hint => {
+                display_hints(game, analyst.player());
+                continue;
+            }
+            Choice::Eval => {
+                display_evaluation(game, analyst.player());
+                continue;
+            }
+            Choice::Moves => {
+                display_moves(game);
+                continue;
+            }
+            Choice::Help => {
+                println!("{}", COMMANDS);
+                continue;
+            }
+        };
+
+        match result {
+            Ok(turn) => return turn,
+            Err(message) => println!("{}", message),
+        }
     }
+}
 
-    Some(Choice::Move {
-        x: x as u8,
-        y: y as u8,
-    })
+/// Parses a command of a human player, ie `f5`, `undo` or `save game.ggf`,
+/// whatever the case.
+fn parse_choice(response: &str) -> Result<Choice, String> {
+    let response = response.trim();
+    let (command, argument) = match response.find(' ') {
+        Some(index) => (&response[..index], response[index + 1..].trim()),
+        None => (response, ""),
+    };
+
+    let choice = match (command.to_lowercase().as_str(), argument) {
+        ("undo", "") => Choice::Undo,
+        ("hint", "") | ("h", "") => Choice::Hint,
+        ("eval", "") => Choice::Eval,
+        ("moves", "") => Choice::Moves,
+        ("save", file) if !file.is_empty() => Choice::Save(file.to_string()),
+        ("load", file) if !file.is_empty() => Choice::Load(file.to_string()),
+        ("save", _) | ("load", _) => {
+            return Err(format!("A file is needed, ie '{} game.ggf'.", command))
+        }
+        ("flip", "") => Choice::Flip,
+        ("help", "") => Choice::Help,
+        ("quit", "") | ("q", "") => Choice::Quit,
+        (square, "") => match parse_square(square) {
+            Ok((x, y)) => Choice::Move { x, y },
+            Err(_) => {
+                return Err(format!(
+                    "Unknown command '{}', type help for the commands.",
+                    response
+                ))
+            }
+        },
+        _ => {
+            return Err(format!(
+                "Unknown command '{}', type help for the commands.",
+                response
+            ))
+        }
+    };
+
+    Ok(choice)
 }
 
-fn read_string() -> String {
+/// Reads a line of the user, None at the end of the input.
+fn read_string() -> Option<String> {
     let mut s = String::new();
-    io::stdin()
+    let read = io::stdin()
         .read_line(&mut s)
         .expect("Unable to read user input.");
+    if read == 0 {
+        return None;
+    }
     trim_newline(&mut s);
 
-    s.trim().to_string()
+    Some(s.trim().to_string())
 }
 
 fn trim_newline(s: &mut String) {
@@ -170,3 +688,35 @@ fn trim_newline(s: &mut String) {
         s.pop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed_whatever_the_case() {
+        assert_eq!(parse_choice("f5"), Ok(Choice::Move { x: 5, y: 4 }));
+        assert_eq!(parse_choice(" A1 "), Ok(Choice::Move { x: 0, y: 0 }));
+        assert_eq!(parse_choice("UNDO"), Ok(Choice::Undo));
+        assert_eq!(parse_choice("h"), Ok(Choice::Hint));
+        assert_eq!(parse_choice("Q"), Ok(Choice::Quit));
+        assert_eq!(parse_choice("moves"), Ok(Choice::Moves));
+        assert_eq!(parse_choice("flip"), Ok(Choice::Flip));
+        assert_eq!(
+            parse_choice("save my game.ggf"),
+            Ok(Choice::Save("my game.ggf".to_string()))
+        );
+        assert_eq!(
+            parse_choice("Load game.ggf"),
+            Ok(Choice::Load("game.ggf".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_commands_are_rejected() {
+        assert!(parse_choice("").is_err());
+        assert!(parse_choice("i9").is_err());
+        assert!(parse_choice("save").is_err());
+        assert!(parse_choice("undo 2").is_err());
+    }
+}
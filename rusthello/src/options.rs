@@ -0,0 +1,433 @@
+use rusthello::{
+    AlphaBeta, BookPlayer, Evaluator, Game, Mcts, Minimax, OpeningBook, ParallelAlphaBeta, Pvs,
+    TimeLimit, VirtualPlayer, WeightedEvaluator,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The maximum depth of a search.
+const MAX_DEPTH: u8 = 60;
+
+/// The count of random games per move of MCTS, without time limit.
+const DEFAULT_PLAYOUTS: u32 = 5000;
+
+/// Positions having at most this count of empty squares are solved exactly
+/// by the alpha-beta engines.
+const ENDGAME_EMPTIES: u8 = 14;
+
+/// The options of a command : the positional arguments, and the named ones,
+/// ie `--depth 6`. All named options take a value.
+#[derive(Clone)]
+pub struct Options {
+    positional: Vec<String>,
+    named: Vec<(String, String)>,
+}
+
+impl Options {
+    /// Parses the arguments of a command, the command itself excluded.
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            positional: Vec::new(),
+            named: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("The option --{} needs a value.", name))?;
+                    if options.get(name).is_some() {
+                        return Err(format!("The option --{} is given twice.", name));
+                    }
+                    options.named.push((name.to_string(), value.to_string()));
+                }
+                None => options.positional.push(arg.to_string()),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Sets the value of a named option, replacing the given one if any.
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.named.retain(|(option, _)| option != name);
+        self.named.push((name.to_string(), value.to_string()));
+    }
+
+    /// Returns the positional arguments.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// Returns the value of a named option.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.named
+            .iter()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of a named option parsed as a number in the given
+    /// range.
+    pub fn get_number<T>(&self, name: &str, range: (T, T)) -> Result<Option<T>, String>
+    where
+        T: std::str::FromStr + PartialOrd + std::fmt::Display,
+    {
+        match self.get(name) {
+            None => Ok(None),
+            Some(value) => match value.parse::<T>() {
+                Ok(number) if number >= range.0 && number <= range.1 => Ok(Some(number)),
+                _ => Err(format!(
+                    "The option --{} must be a number from {} to {}, not '{}'.",
+                    name, range.0, range.1, value
+                )),
+            },
+        }
+    }
+
+    /// Checks that only the given named options, and at most the given
+    /// count of positional arguments, are used.
+    pub fn check(&self, names: &[&str], max_positional: usize) -> Result<(), String> {
+        if let Some((name, _)) = self
+            .named
+            .iter()
+            .find(|(name, _)| !names.contains(&name.as_str()))
+        {
+            return Err(format!("Unknown option --{}.", name));
+        }
+        if self.positional.len() > max_positional {
+            return Err(format!(
+                "Unexpected argument '{}'.",
+                self.positional[max_positional]
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the game given by `--position` (see `Game::to_position`) or
+    /// `--moves` (a transcript), or a new game.
+    pub fn game(&self) -> Result<Game, String> {
+        match (self.get("position"), self.get("moves")) {
+            (Some(_), Some(_)) => Err("Either --position or --moves can be given.".to_string()),
+            (Some(position), None) => Game::from_position(position),
+            (None, Some(transcript)) => Game::from_transcript(transcript),
+            (None, None) => Ok(Game::new()),
+        }
+    }
+}
+
+/// The names of the options of a game, see `Options::game`.
+pub const GAME_OPTIONS: [&str; 2] = ["position", "moves"];
+
+/// The names of the options of an engine, see `EngineOptions`.
+pub const ENGINE_OPTIONS: [&str; 8] = [
+    "engine",
+    "depth",
+    "time",
+    "playouts",
+    "evaluator",
+    "threads",
+    "book",
+    "seed",
+];
+
+/// The search algorithms.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EngineKind {
+    AlphaBeta,
+    Parallel,
+    Pvs,
+    Minimax,
+    Mcts,
+}
+
+impl EngineKind {
+    /// Returns the name of the engine, as given to `--engine`.
+    pub fn name(self) -> &'static str {
+        match self {
+            EngineKind::AlphaBeta => "alphabeta",
+            EngineKind::Parallel => "parallel",
+            EngineKind::Pvs => "pvs",
+            EngineKind::Minimax => "minimax",
+            EngineKind::Mcts => "mcts",
+        }
+    }
+}
+
+/// The evaluators of positions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EvaluatorKind {
+    /// The default weighted evaluator.
+    Simple,
+    /// The classic weights, with mobility and frontier terms.
+    Classic,
+}
+
+/// The configuration of a virtual player.
+#[derive(Clone)]
+pub struct EngineOptions {
+    pub engine: EngineKind,
+    pub depth: u8,
+    pub time: Option<Duration>,
+    pub playouts: u32,
+    pub evaluator: EvaluatorKind,
+    pub threads: Option<usize>,
+    pub book: Option<OpeningBook>,
+    pub seed: u64,
+}
+
+impl EngineOptions {
+    /// Reads the configuration of a virtual player from the options, the
+    /// given depth being used when `--depth` isn't given.
+    pub fn from_options(options: &Options, default_depth: u8) -> Result<Self, String> {
+        let time = options
+            .get_number::<f64>("time", (0.1, 3600.0))?
+            .map(Duration::from_secs_f64);
+        let engine = match options.get("engine") {
+            None if time.is_some() => EngineKind::AlphaBeta,
+            None => EngineKind::Parallel,
+            Some("alphabeta") => EngineKind::AlphaBeta,
+            Some("parallel") => EngineKind::Parallel,
+            Some("pvs") => EngineKind::Pvs,
+            Some("minimax") => EngineKind::Minimax,
+            Some("mcts") => EngineKind::Mcts,
+            Some(other) => return Err(format!("Unknown engine '{}'.", other)),
+        };
+        let evaluator = match options.get("evaluator") {
+            None | Some("simple") => EvaluatorKind::Simple,
+            Some("classic") => EvaluatorKind::Classic,
+            Some(other) => return Err(format!("Unknown evaluator '{}'.", other)),
+        };
+        let depth = options.get_number("depth", (1, MAX_DEPTH))?;
+        let playouts = options.get_number("playouts", (1, 10_000_000))?;
+        let threads = options.get_number("threads", (1, 256))?;
+
+        let incompatible_options = [
+            (
+                "time",
+                !matches!(engine, EngineKind::AlphaBeta | EngineKind::Mcts),
+            ),
+            ("playouts", engine != EngineKind::Mcts),
+            ("depth", engine == EngineKind::Mcts),
+            ("evaluator", engine == EngineKind::Mcts),
+            ("threads", engine != EngineKind::Parallel),
+        ];
+        if let Some((name, _)) = incompatible_options
+            .iter()
+            .find(|&&(name, incompatible)| incompatible && options.get(name).is_some())
+        {
+            return Err(format!(
+                "The option --{} can't be used with the {} engine.",
+                name,
+                engine.name()
+            ));
+        }
+
+        let book = match options.get("book") {
+            None => None,
+            Some(path) => Some(OpeningBook::from_file(path)?),
+        };
+        let seed = match options.get_number("seed", (0, u64::MAX))? {
+            Some(seed) => seed,
+            None => time_seed(),
+        };
+
+        Ok(EngineOptions {
+            engine,
+            depth: depth.unwrap_or(if time.is_some() {
+                MAX_DEPTH
+            } else {
+                default_depth
+            }),
+            time,
+            // With a time limit, the playouts are only limited by the time.
+            playouts: playouts.unwrap_or(if time.is_some() {
+                u32::MAX
+            } else {
+                DEFAULT_PLAYOUTS
+            }),
+            evaluator,
+            threads,
+            book,
+            seed,
+        })
+    }
+
+    /// Reads the configuration of a virtual player from a level : a depth
+    /// (ie `6`), a time limit in seconds (ie `5s`), or a count of random
+    /// games for MCTS (ie `5000p`). The level can be prefixed by an engine
+    /// (ie `pvs:8`), or be only an engine (ie `mcts`). The other options
+    /// are read from `options`.
+    pub fn from_level(options: &Options, level: &str, default_depth: u8) -> Result<Self, String> {
+        let (engine, depth) = match level.find(':') {
+            Some(index) => (Some(&level[..index]), &level[index + 1..]),
+            None if level.starts_with(|c: char| c.is_ascii_digit()) => (None, level),
+            None => (Some(level), ""),
+        };
+
+        let mut options = options.clone();
+        if let Some(engine) = engine {
+            options.set("engine", engine);
+        }
+        if let Some(seconds) = depth.strip_suffix('s') {
+            options.set("time", seconds);
+        } else if let Some(playouts) = depth.strip_suffix('p') {
+            if engine.is_none() {
+                options.set("engine", "mcts");
+            }
+            options.set("playouts", playouts);
+        } else if !depth.is_empty() {
+            options.set("depth", depth);
+        }
+
+        Self::from_options(&options, default_depth)
+            .map_err(|message| format!("Invalid level '{}' : {}", level, message))
+    }
+
+    /// Builds the virtual player.
+    pub fn build(self) -> Box<dyn VirtualPlayer> {
+        let player = match self.evaluator {
+            EvaluatorKind::Simple => self.build_with(WeightedEvaluator::default()),
+            EvaluatorKind::Classic => self.build_with(WeightedEvaluator::classic()),
+        };
+        match self.book {
+            None => player,
+            Some(book) => Box::new(BookPlayer::new(book, player).with_seed(self.seed)),
+        }
+    }
+
+    fn build_with<E>(&self, evaluator: E) -> Box<dyn VirtualPlayer>
+    where
+        E: Evaluator + Clone + Send + 'static,
+    {
+        let time_limit = self.time.map(TimeLimit::new);
+        match self.engine {
+            EngineKind::AlphaBeta => {
+                let player = AlphaBeta::with_evaluator(self.depth, evaluator)
+                    .with_endgame_solver(ENDGAME_EMPTIES);
+                match time_limit {
+                    None => Box::new(player),
+                    Some(time_limit) => Box::new(player.with_time_limit(time_limit)),
+                }
+            }
+            EngineKind::Parallel => {
+                let player = ParallelAlphaBeta::with_evaluator(self.depth, evaluator)
+                    .with_endgame_solver(ENDGAME_EMPTIES);
+                match self.threads {
+                    None => Box::new(player),
+                    Some(threads) => Box::new(player.with_threads(threads)),
+                }
+            }
+            EngineKind::Pvs => Box::new(Pvs::with_evaluator(self.depth, evaluator)),
+            EngineKind::Minimax => Box::new(Minimax::with_evaluator(self.depth, evaluator)),
+            EngineKind::Mcts => {
+                let player = Mcts::new(self.playouts).with_seed(self.seed);
+                match time_limit {
+                    None => Box::new(player),
+                    Some(time_limit) => Box::new(player.with_time_limit(time_limit)),
+                }
+            }
+        }
+    }
+}
+
+/// Returns a seed changing at each run, for the computer to vary its
+/// openings.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &str) -> Options {
+        let args: Vec<String> = args.split_whitespace().map(str::to_string).collect();
+        Options::parse(&args).unwrap()
+    }
+
+    #[test]
+    fn options_are_named_or_positional() {
+        let options = options("6 --depth 4 --moves f5d6 x");
+        assert_eq!(options.positional(), &["6".to_string(), "x".to_string()]);
+        assert_eq!(options.get("depth"), Some("4"));
+        assert_eq!(options.get("seed"), None);
+        assert!(options.check(&["depth", "moves"], 2).is_ok());
+        assert!(options.check(&["depth", "moves"], 1).is_err());
+        assert!(options.check(&["depth"], 2).is_err());
+        assert_eq!(
+            options.game().unwrap().to_transcript(),
+            Ok("f5d6".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let args = vec!["--depth".to_string()];
+        assert!(Options::parse(&args).is_err());
+        let args: Vec<String> = vec!["--seed", "1", "--seed", "2"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        assert!(Options::parse(&args).is_err());
+        assert!(options("--depth 0").get_number("depth", (1, 60)).is_err());
+        assert!(options("--depth x")
+            .get_number::<u8>("depth", (1, 60))
+            .is_err());
+        assert!(options("--position x --moves f5").game().is_err());
+    }
+
+    #[test]
+    fn engine_options_have_defaults() {
+        let engine = EngineOptions::from_options(&options(""), 6).unwrap();
+        assert_eq!(engine.engine, EngineKind::Parallel);
+        assert_eq!(engine.depth, 6);
+        assert_eq!(engine.evaluator, EvaluatorKind::Simple);
+        let engine = EngineOptions::from_options(&options("--time 2"), 6).unwrap();
+        assert_eq!(engine.engine, EngineKind::AlphaBeta);
+        assert_eq!(engine.time, Some(Duration::from_secs(2)));
+        assert_eq!(engine.depth, MAX_DEPTH);
+    }
+
+    #[test]
+    fn engine_options_are_checked() {
+        let from = |args| EngineOptions::from_options(&options(args), 6);
+        assert!(from("--engine pvs --evaluator classic --depth 4 --seed 3").is_ok());
+        assert!(from("--engine mcts --playouts 100 --time 1").is_ok());
+        assert!(from("--engine deep").is_err());
+        assert!(from("--evaluator best").is_err());
+        assert!(from("--engine pvs --time 1").is_err());
+        assert!(from("--engine mcts --depth 4").is_err());
+        assert!(from("--engine alphabeta --threads 2").is_err());
+        assert!(from("--book missing.txt").is_err());
+    }
+
+    #[test]
+    fn engine_options_are_read_from_levels() {
+        let from = |level| EngineOptions::from_level(&options(""), level, 6);
+        let engine = from("8").unwrap();
+        assert_eq!((engine.engine, engine.depth), (EngineKind::Parallel, 8));
+        let engine = from("5s").unwrap();
+        assert_eq!(engine.time, Some(Duration::from_secs(5)));
+        let engine = from("300p").unwrap();
+        assert_eq!((engine.engine, engine.playouts), (EngineKind::Mcts, 300));
+        let engine = from("pvs:4").unwrap();
+        assert_eq!((engine.engine, engine.depth), (EngineKind::Pvs, 4));
+        let engine = from("minimax").unwrap();
+        assert_eq!((engine.engine, engine.depth), (EngineKind::Minimax, 6));
+        let engine = from("mcts:2s").unwrap();
+        assert_eq!(engine.time, Some(Duration::from_secs(2)));
+        assert!(from("fast").is_err());
+        assert!(from("pvs:5s").is_err());
+
+        let shared = options("--evaluator classic --depth 3");
+        let engine = EngineOptions::from_level(&shared, "alphabeta", 6).unwrap();
+        assert_eq!(engine.evaluator, EvaluatorKind::Classic);
+        assert_eq!((engine.engine, engine.depth), (EngineKind::AlphaBeta, 3));
+        let engine = EngineOptions::from_level(&shared, "5", 6).unwrap();
+        assert_eq!(engine.depth, 5);
+    }
+}
@@ -1,8 +1,40 @@
+#[cfg(test)]
+mod array_board;
 mod board;
+mod endgame;
+mod evaluation;
 mod game;
 mod game_status;
+mod gtp;
+mod mcts;
+mod move_ordering;
+mod nboard;
+mod notation;
+mod opening_book;
+mod pvs;
+mod random;
+mod search;
+mod symmetry;
+#[cfg(test)]
+mod test_support;
+mod time_limit;
+mod tournament;
+mod transposition_table;
 mod virtual_player;
+mod zobrist;
 
 pub use self::board::*;
+pub use self::endgame::*;
+pub use self::evaluation::*;
 pub use self::game::*;
+pub use self::gtp::*;
+pub use self::mcts::*;
+pub use self::nboard::*;
+pub use self::notation::*;
+pub use self::opening_book::*;
+pub use self::pvs::*;
+pub use self::symmetry::*;
+pub use self::time_limit::*;
+pub use self::tournament::*;
+pub use self::transposition_table::*;
 pub use self::virtual_player::*;
@@ -0,0 +1,250 @@
+//! The original array based board, kept as a reference implementation to
+//! check the bitboard one (see differential tests in `board.rs`).
+
+use super::board::{GridIterator, Player};
+
+/// An Othello board storing each cell in an array, implementing moves by
+/// walking the grid direction by direction.
+#[derive(Debug, Copy, Clone)]
+pub struct ArrayBoard {
+    cells: [[Option<Player>; 8]; 8],
+}
+
+impl ArrayBoard {
+    /// Creates an empty board.
+    pub fn new() -> ArrayBoard {
+        ArrayBoard {
+            cells: [[None; 8]; 8],
+        }
+    }
+
+    /// Creates a new board ready to start a game.
+    pub fn new_start() -> ArrayBoard {
+        let mut board = Self::new();
+        board.set_piece(3, 3, Some(Player::White)).unwrap();
+        board.set_piece(4, 4, Some(Player::White)).unwrap();
+        board.set_piece(3, 4, Some(Player::Black)).unwrap();
+        board.set_piece(4, 3, Some(Player::Black)).unwrap();
+        board
+    }
+
+    /// Sets the content of a board cell.
+    pub fn set_piece(&mut self, x: u8, y: u8, piece: Option<Player>) -> Result<(), String> {
+        Self::check_coordinates(x, y)?;
+        self.cells[x as usize][y as usize] = piece;
+        Ok(())
+    }
+
+    //// Gets the content of a board cell.
+    pub fn get_piece(&self, x: u8, y: u8) -> Result<Option<Player>, String> {
+        Self::check_coordinates(x, y)?;
+        Ok(self.cells[x as usize][y as usize])
+    }
+
+    fn check_coordinates(x: u8, y: u8) -> Result<(), String> {
+        if x > 7 || y > 7 {
+            Err(format!(
+                "the given coordinates are out of range : ({}, {})",
+                x, y
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// All possible directions to capture opponent pieces.
+    const ALL_DIRECTIONS: [(i8, i8); 8] = [
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+        (-1, -1),
+    ];
+
+    /// Checks if the given player can move to the given coordinates.
+    pub fn is_move_valid(&self, player: Player, x: u8, y: u8) -> Result<bool, String> {
+        Self::check_coordinates(x, y)?;
+
+        // Only moves targeting empty cells are valids.
+        if self.cells[x as usize][y as usize].is_some() {
+            return Ok(false);
+        }
+
+        let other_player = player.opponent();
+        let can_capture = Self::ALL_DIRECTIONS
+            .iter()
+            .any(|direction| self.can_capture(other_player, x, y, *direction).is_some());
+
+        Ok(can_capture)
+    }
+
+    /// Checks if a capture is possible for a given move and a given direction.
+    /// Returns a CellsNavigator ready to capture all opponent pieces backward.
+    fn can_capture(
+        &self,
+        opponent: Player,
+        x: u8,
+        y: u8,
+        direction: (i8, i8),
+    ) -> Option<CellsNavigator> {
+        let mut navigator = CellsNavigator::new((x, y), direction).unwrap();
+        let mut found_other_on_path = false;
+        let mut can_capture = false;
+        for position in &mut navigator {
+            let piece = self.cells[position.0 as usize][position.1 as usize];
+            match piece {
+                // Not a valid move.
+                None => break,
+                // Perhaps a valid move.
+                Some(p) if p == opponent => found_other_on_path = true,
+                // If player passes over opponent's pieces and reach a cell containing
+                // one of his pieces, he can capture opponent's pieces (hence it's a valid move).
+                Some(_) => {
+                    can_capture = found_other_on_path;
+                    break;
+                }
+            }
+        }
+        if can_capture {
+            navigator.reverse();
+            return Some(navigator);
+        }
+
+        None
+    }
+
+    /// Plays at the given position for the given player.
+    /// If the move is valid a new Board is returned, else None.
+    pub fn play(&self, player: Player, x: u8, y: u8) -> Result<Option<ArrayBoard>, String> {
+        Self::check_coordinates(x, y)?;
+
+        // Only moves targeting empty cells are valids.
+        if self.cells[x as usize][y as usize].is_some() {
+            return Ok(None);
+        }
+
+        // Explores the 8 possible directions and try to capture opponent pieces.
+        // If at least one capture is possible, the move is valid.
+        let mut new_board = *self;
+        let other_player = player.opponent();
+        let mut valid_move = false;
+        for direction in Self::ALL_DIRECTIONS.iter() {
+            if let Some(navigator) = self.can_capture(other_player, x, y, *direction) {
+                // Let's capture opponent's pieces going backward.
+                valid_move = true;
+                for position in navigator {
+                    // reverse iteration stop at move position
+                    if position == (x, y) {
+                        break;
+                    }
+                    new_board.cells[position.0 as usize][position.1 as usize] = Some(player);
+                }
+            }
+        }
+
+        if valid_move {
+            new_board.cells[x as usize][y as usize] = Some(player);
+            Ok(Some(new_board))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Cheks if a given player can move in at least one position.
+    pub fn can_player_move(&self, player: Player) -> bool {
+        GridIterator::new().any(|(x, y)| self.is_move_valid(player, x, y).unwrap())
+    }
+
+    /// Count the pieces on the board.
+    /// It returns a tuple with black pieces count as the first item,
+    /// and white pieces count as the second.
+    pub fn count_pieces(&self) -> (u8, u8) {
+        let mut black_pieces = 0;
+        let mut white_pieces = 0;
+        for piece in self.cells.iter().flatten() {
+            match piece {
+                Some(Player::Black) => black_pieces += 1,
+                Some(Player::White) => white_pieces += 1,
+                _ => (),
+            }
+        }
+
+        (black_pieces, white_pieces)
+    }
+}
+
+/// Iterator to navigate from a start position upto the limit of a board in
+/// a given direction.
+/// The start position is excluded from the iteration.
+/// The iterator can be reversed to go backward.
+#[derive(Debug)]
+struct CellsNavigator {
+    current_position: (i8, i8),
+    direction: (i8, i8),
+}
+
+impl CellsNavigator {
+    fn new(start: (u8, u8), direction: (i8, i8)) -> Result<CellsNavigator, String> {
+        let (x, y) = start;
+        let (dx, dy) = direction;
+
+        ArrayBoard::check_coordinates(x, y)?;
+
+        if !(-1..=1).contains(&dx) || !(-1..=1).contains(&dy) {
+            return Err(format!(
+                "the given direction is out of range : ({}, {})",
+                dx, dy
+            ));
+        }
+
+        Ok(CellsNavigator {
+            current_position: (x as i8, y as i8),
+            direction,
+        })
+    }
+
+    fn reverse(&mut self) {
+        self.direction = (-self.direction.0, -self.direction.1);
+    }
+}
+
+impl Iterator for CellsNavigator {
+    type Item = (u8, u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.current_position;
+        let (dx, dy) = self.direction;
+        let (x, y) = (x + dx, y + dy);
+        if !(0..=7).contains(&x) || !(0..=7).contains(&y) {
+            None
+        } else {
+            self.current_position = (x, y);
+            Some((x as u8, y as u8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_navigation() {
+        let mut cn = CellsNavigator::new((3, 3), (1, -1)).unwrap();
+        assert_eq!(cn.next(), Some((4, 2)));
+        assert_eq!(cn.next(), Some((5, 1)));
+        assert_eq!(cn.next(), Some((6, 0)));
+        assert_eq!(cn.next(), None);
+    }
+
+    #[test]
+    fn cell_navigation_reverse() {
+        let mut cn = CellsNavigator::new((3, 3), (1, -1)).unwrap();
+        assert_eq!(cn.next(), Some((4, 2)));
+        cn.reverse();
+        assert_eq!(cn.next(), Some((3, 3)));
+    }
+}
@@ -1,7 +1,9 @@
+use super::zobrist;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /// Othello players.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Player {
     Black,
     White,
@@ -29,16 +31,39 @@ impl fmt::Display for Player {
 
 /// An Othello board, implementing moves.
 /// Board does not implement game workflow.
-#[derive(Debug, Copy, Clone)]
+///
+/// The board is made of two bitboards, one per player. The bit `x + 8 * y`
+/// is set when the player owns the piece at the coordinates `(x, y)`.
+///
+/// The board also maintains the Zobrist hash of its pieces across moves,
+/// making boards cheap to use as keys of hash maps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Board {
-    cells: [[Option<Player>; 8]; 8],
+    black: u64,
+    white: u64,
+    hash: u64,
+}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal boards have the same pieces, hence the same Zobrist hash.
+        state.write_u64(self.hash);
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Board {
     /// Creates an empty board.
     pub fn new() -> Board {
         Board {
-            cells: [[None; 8]; 8],
+            black: 0,
+            white: 0,
+            hash: 0,
         }
     }
 
@@ -55,14 +80,35 @@ impl Board {
     /// Sets the content of a board cell.
     pub fn set_piece(&mut self, x: u8, y: u8, piece: Option<Player>) -> Result<(), String> {
         Self::check_coordinates(x, y)?;
-        self.cells[x as usize][y as usize] = piece;
+        let mask = Self::mask(x, y);
+        let index = mask.trailing_zeros();
+        if let Some(previous) = self.get_piece(x, y)? {
+            self.hash ^= zobrist::piece_key(previous, index);
+        }
+        self.black &= !mask;
+        self.white &= !mask;
+        match piece {
+            Some(Player::Black) => self.black |= mask,
+            Some(Player::White) => self.white |= mask,
+            None => (),
+        }
+        if let Some(player) = piece {
+            self.hash ^= zobrist::piece_key(player, index);
+        }
         Ok(())
     }
 
     //// Gets the content of a board cell.
     pub fn get_piece(&self, x: u8, y: u8) -> Result<Option<Player>, String> {
         Self::check_coordinates(x, y)?;
-        Ok(self.cells[x as usize][y as usize])
+        let mask = Self::mask(x, y);
+        if self.black & mask != 0 {
+            Ok(Some(Player::Black))
+        } else if self.white & mask != 0 {
+            Ok(Some(Player::White))
+        } else {
+            Ok(None)
+        }
     }
 
     fn check_coordinates(x: u8, y: u8) -> Result<(), String> {
@@ -76,79 +122,105 @@ impl Board {
         }
     }
 
+    /// Returns the bitboard mask of the given (valid) coordinates.
+    fn mask(x: u8, y: u8) -> u64 {
+        1 << (x + 8 * y)
+    }
+
+    /// Returns the bitboard of the given player pieces.
+    /// The bit `x + 8 * y` is set when the player owns the piece at `(x, y)`.
+    pub fn bitboard(&self, player: Player) -> u64 {
+        match player {
+            Player::Black => self.black,
+            Player::White => self.white,
+        }
+    }
+
+    /// Creates a board from the bitboards of both players, see `bitboard`.
+    pub fn from_bitboards(black: u64, white: u64) -> Result<Board, String> {
+        if black & white != 0 {
+            return Err(format!("the bitboards overlap : {:#018x}", black & white));
+        }
+        Ok(Board {
+            black,
+            white,
+            hash: zobrist::pieces_hash(black, white),
+        })
+    }
+
+    /// Returns the Zobrist hash of the position made of the board and the
+    /// next player. It's maintained incrementally, hence costs nothing.
+    pub fn zobrist_hash(&self, next_player: Player) -> u64 {
+        self.hash ^ zobrist::player_key(next_player)
+    }
+
+    /// Returns the bitboard of empty cells.
+    fn empty_cells(&self) -> u64 {
+        !(self.black | self.white)
+    }
+
     /// Returns an iterator on the board.
     /// The iterator will returns all cells positions and their contents.
-    pub fn iter(self: &Board) -> BoardIterator {
+    pub fn iter(&self) -> BoardIterator<'_> {
         BoardIterator::new(self)
     }
 
-    /// All possible directions to capture opponent pieces.
-    const ALL_DIRECTIONS: [(i8, i8); 8] = [
-        (0, -1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-        (0, 1),
-        (-1, 1),
-        (-1, 0),
-        (-1, -1),
-    ];
-
     /// Checks if the given player can move to the given coordinates.
     /// It's faster than play as it does just the bare minimum.
     pub fn is_move_valid(&self, player: Player, x: u8, y: u8) -> Result<bool, String> {
         Self::check_coordinates(x, y)?;
+        Ok(self.legal_moves_mask(player) & Self::mask(x, y) != 0)
+    }
 
-        // Only moves targeting empty cells are valids.
-        if self.cells[x as usize][y as usize] != None {
-            return Ok(false);
-        }
+    /// Returns an iterator over all positions where the given player can
+    /// move, in the same order as GridIterator.
+    pub fn legal_moves(&self, player: Player) -> MovesIterator {
+        MovesIterator::new(self.legal_moves_mask(player))
+    }
 
-        let other_player = player.opponent();
+    /// Returns all positions where the given player can move as a bitmask.
+    /// The bit `x + 8 * y` is set when the move at `(x, y)` is valid.
+    pub fn legal_moves_mask(&self, player: Player) -> u64 {
+        let own = self.bitboard(player);
+        let opponent = self.bitboard(player.opponent());
+        let empty = self.empty_cells();
 
-        for direction in Self::ALL_DIRECTIONS.iter() {
-            if let Some(_) = self.can_capture(other_player, x, y, *direction) {
-                return Ok(true);
+        Direction::ALL.iter().fold(0, |moves, direction| {
+            // Follows lines of opponent pieces starting next to the player
+            // ones. A line can't be longer than 6 pieces.
+            let mut line = direction.shift(own) & opponent;
+            for _ in 0..5 {
+                line |= direction.shift(line) & opponent;
             }
-        }
+            moves | (direction.shift(line) & empty)
+        })
+    }
 
-        // No capture is possible in any direction : invalid move
-        Ok(false)
-    }
-
-    /// Checks if a capture is possible for a given move and a given direction.
-    /// Returns a CellsNavigator ready to capture all opponent pieces backward.
-    fn can_capture(
-        &self,
-        opponent: Player,
-        x: u8,
-        y: u8,
-        direction: (i8, i8),
-    ) -> Option<CellsNavigator> {
-        let mut navigator = CellsNavigator::new((x, y), direction).unwrap();
-        let mut found_other_on_path = false;
-        let mut can_capture = false;
-        for position in &mut navigator {
-            let piece = self.cells[position.0 as usize][position.1 as usize];
-            match piece {
-                // Not a valid move.
-                None => break,
-                // Perhaps a valid move.
-                Some(p) if p == opponent => found_other_on_path = true,
-                // If player passes over opponent's pieces and reach a cell containing
-                // one of his pieces, he can capture opponent's pieces (hence it's a valid move).
-                Some(_) => {
-                    can_capture = found_other_on_path;
-                    break;
-                }
-            }
-        }
-        if can_capture {
-            navigator.reverse();
-            return Some(navigator);
+    /// Returns the bitboard of the opponent pieces captured if the given
+    /// player moves at the given position (as a mask), or 0 if the move is
+    /// not valid.
+    fn captures(&self, player: Player, mask: u64) -> u64 {
+        if self.empty_cells() & mask == 0 {
+            return 0;
         }
 
-        None
+        let own = self.bitboard(player);
+        let opponent = self.bitboard(player.opponent());
+
+        Direction::ALL.iter().fold(0, |captures, direction| {
+            let mut line = 0;
+            let mut cursor = direction.shift(mask);
+            while cursor & opponent != 0 {
+                line |= cursor;
+                cursor = direction.shift(cursor);
+            }
+            // The line is captured only if it ends with a player piece.
+            if cursor & own != 0 {
+                captures | line
+            } else {
+                captures
+            }
+        })
     }
 
     /// Plays at the given position for the given player.
@@ -156,65 +228,137 @@ impl Board {
     pub fn play(&self, player: Player, x: u8, y: u8) -> Result<Option<Board>, String> {
         Self::check_coordinates(x, y)?;
 
-        // Only moves targeting empty cells are valids.
-        if self.cells[x as usize][y as usize] != None {
+        let mask = Self::mask(x, y);
+        let captures = self.captures(player, mask);
+        if captures == 0 {
             return Ok(None);
         }
 
-        // Explores the 8 possible directions and try to capture opponent pieces.
-        // If at least one capture is possible, the move is valid.
-        let mut new_board = self.clone();
-        let other_player = player.opponent();
-        let mut valid_move = false;
-        for direction in Self::ALL_DIRECTIONS.iter() {
-            if let Some(navigator) = self.can_capture(other_player, x, y, *direction) {
-                // Let's capture opponent's pieces going backward.
-                valid_move = true;
-                for position in navigator {
-                    // reverse iteration stop at move position
-                    if position == (x, y) {
-                        break;
-                    }
-                    new_board.cells[position.0 as usize][position.1 as usize] = Some(player);
-                }
+        let changes = captures | mask;
+        let mut new_board = *self;
+        match player {
+            Player::Black => {
+                new_board.black |= changes;
+                new_board.white &= !changes;
+            }
+            Player::White => {
+                new_board.white |= changes;
+                new_board.black &= !changes;
             }
         }
-
-        if valid_move {
-            new_board.cells[x as usize][y as usize] = Some(player);
-            Ok(Some(new_board))
-        } else {
-            Ok(None)
+        new_board.hash ^= zobrist::piece_key(player, mask.trailing_zeros());
+        let mut flipped = captures;
+        while flipped != 0 {
+            new_board.hash ^= zobrist::flip_key(flipped.trailing_zeros());
+            flipped &= flipped - 1;
         }
+
+        Ok(Some(new_board))
     }
 
     /// Cheks if a given player can move in at least one position.
     pub fn can_player_move(&self, player: Player) -> bool {
-        for (x, y) in GridIterator::new() {
-            let can_move = self.is_move_valid(player, x, y).unwrap();
-            if can_move {
-                return true;
-            }
-        }
+        self.legal_moves_mask(player) != 0
+    }
+
+    /// Returns the mobility of the given player, ie the count of positions
+    /// where he can move.
+    pub fn mobility(&self, player: Player) -> u8 {
+        self.legal_moves_mask(player).count_ones() as u8
+    }
+
+    /// Returns the count of frontier pieces of the given player, ie pieces
+    /// next to at least one empty cell.
+    pub fn frontier(&self, player: Player) -> u8 {
+        let empty = self.empty_cells();
+        let next_to_empty = Direction::ALL
+            .iter()
+            .fold(0, |cells, direction| cells | direction.shift(empty));
+        (self.bitboard(player) & next_to_empty).count_ones() as u8
+    }
 
-        false
+    /// Counts the positions reached after the given count of plies, the
+    /// given player being the next to move. A player who can't move passes,
+    /// which counts as a ply, and finished games are counted as reached
+    /// positions. It's useful to check the moves generation against known
+    /// counts.
+    pub fn perft(&self, player: Player, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves_mask(player);
+        if moves == 0 {
+            return if self.can_player_move(player.opponent()) {
+                self.perft(player.opponent(), depth - 1)
+            } else {
+                1
+            };
+        }
+        if depth == 1 {
+            return moves.count_ones() as u64;
+        }
+        MovesIterator::new(moves)
+            .map(|(x, y)| {
+                self.play(player, x, y)
+                    .unwrap()
+                    .unwrap()
+                    .perft(player.opponent(), depth - 1)
+            })
+            .sum()
     }
 
     /// Count the pieces on the board.
     /// It returns a tuple with black pieces count as the first item,
     /// and white pieces count as the second.
     pub fn count_pieces(&self) -> (u8, u8) {
-        let mut black_pieces = 0;
-        let mut white_pieces = 0;
-        for (_, _, piece) in self.iter() {
-            match piece {
-                Some(Player::Black) => black_pieces += 1,
-                Some(Player::White) => white_pieces += 1,
-                _ => (),
-            }
-        }
+        (self.black.count_ones() as u8, self.white.count_ones() as u8)
+    }
+}
+
+/// The eight directions used to capture pieces, as bitboard shifts.
+#[derive(Debug, Copy, Clone)]
+enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All possible directions to capture opponent pieces.
+    const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// All cells except the ones of the `A` column.
+    const NOT_A_COLUMN: u64 = 0xfefe_fefe_fefe_fefe;
+    /// All cells except the ones of the `H` column.
+    const NOT_H_COLUMN: u64 = 0x7f7f_7f7f_7f7f_7f7f;
 
-        (black_pieces, white_pieces)
+    /// Moves all pieces of the bitboard one cell in the direction. Pieces
+    /// going out of the board are lost, they never wrap to the other side.
+    fn shift(self, bitboard: u64) -> u64 {
+        match self {
+            Direction::North => bitboard >> 8,
+            Direction::NorthEast => (bitboard >> 7) & Self::NOT_A_COLUMN,
+            Direction::East => (bitboard << 1) & Self::NOT_A_COLUMN,
+            Direction::SouthEast => (bitboard << 9) & Self::NOT_A_COLUMN,
+            Direction::South => bitboard << 8,
+            Direction::SouthWest => (bitboard << 7) & Self::NOT_H_COLUMN,
+            Direction::West => (bitboard >> 1) & Self::NOT_H_COLUMN,
+            Direction::NorthWest => (bitboard >> 9) & Self::NOT_H_COLUMN,
+        }
     }
 }
 
@@ -267,6 +411,41 @@ impl Iterator for BoardIterator<'_> {
     }
 }
 
+/// Implements an iterator over the positions of a moves bitmask.
+#[derive(Debug, Copy, Clone)]
+pub struct MovesIterator {
+    moves: u64,
+}
+
+impl MovesIterator {
+    fn new(moves: u64) -> Self {
+        MovesIterator { moves }
+    }
+}
+
+impl Iterator for MovesIterator {
+    type Item = (u8, u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.moves == 0 {
+            return None;
+        }
+
+        let index = self.moves.trailing_zeros() as u8;
+        // clear the lowest bit set
+        self.moves &= self.moves - 1;
+
+        Some((index % 8, index / 8))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.moves.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for MovesIterator {}
+
 /// An iterator over a 8x8 grid
 #[derive(Debug)]
 pub struct GridIterator {
@@ -280,6 +459,12 @@ impl GridIterator {
     }
 }
 
+impl Default for GridIterator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Iterator for GridIterator {
     type Item = (u8, u8);
 
@@ -299,80 +484,27 @@ impl Iterator for GridIterator {
     }
 }
 
-/// Iterator to navigate from a start position upto the limit of a board in
-/// a given direction.
-/// The start position is excluded from the iteration.
-/// The iterator can be reversed to go backward.
-#[derive(Debug)]
-struct CellsNavigator {
-    current_position: (i8, i8),
-    direction: (i8, i8),
-}
-
-impl CellsNavigator {
-    fn new(start: (u8, u8), direction: (i8, i8)) -> Result<CellsNavigator, String> {
-        let (x, y) = start;
-        let (dx, dy) = direction;
-
-        Board::check_coordinates(x, y)?;
-
-        if !(-1..=1).contains(&dx) || !(-1..=1).contains(&dy) {
-            return Err(format!(
-                "the given direction is out of range : ({}, {})",
-                dx, dy
-            ));
-        }
-
-        Ok(CellsNavigator {
-            current_position: (x as i8, y as i8),
-            direction: direction,
-        })
-    }
-
-    fn reverse(&mut self) {
-        self.direction = (-self.direction.0, -self.direction.1);
-    }
-}
-
-impl Iterator for CellsNavigator {
-    type Item = (u8, u8);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let (x, y) = self.current_position;
-        let (dx, dy) = self.direction;
-        let (x, y) = (x + dx, y + dy);
-        if x < 0 || x > 7 || y < 0 || y > 7 {
-            None
-        } else {
-            self.current_position = (x, y);
-            Some((x as u8, y as u8))
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn new_creates_empty_board() {
         let board = Board::new();
-        board.cells.iter().flatten().for_each(|piece| {
-            assert_eq!(piece.is_none(), true);
+        board.iter().for_each(|(_, _, piece)| {
+            assert!(piece.is_none());
         })
     }
 
     #[test]
     fn new_start_creates_a_ready_to_play_board() {
         let board = Board::new_start();
-        for (x, columns) in board.cells.iter().enumerate() {
-            for (y, piece) in columns.iter().enumerate() {
-                if x < 3 || x > 4 || y < 3 || y > 4 {
-                    assert_eq!(piece.is_none(), true);
-                } else if x == y {
-                    assert_eq!(*piece, Some(Player::White));
-                } else {
-                    assert_eq!(*piece, Some(Player::Black));
-                }
+        for (x, y, piece) in board.iter() {
+            if !(3..=4).contains(&x) || !(3..=4).contains(&y) {
+                assert!(piece.is_none());
+            } else if x == y {
+                assert_eq!(piece, Some(Player::White));
+            } else {
+                assert_eq!(piece, Some(Player::Black));
             }
         }
     }
@@ -381,13 +513,25 @@ mod tests {
     fn set_piece() {
         let mut board = Board::new();
         board.set_piece(1, 2, Some(Player::Black)).unwrap();
-        assert_eq!(board.cells[1][2], Some(Player::Black))
+        assert_eq!(board.black, 1 << (1 + 8 * 2));
+        assert_eq!(board.white, 0);
+    }
+
+    #[test]
+    fn set_piece_replaces_or_removes_a_piece() {
+        let mut board = Board::new();
+        board.set_piece(1, 2, Some(Player::Black)).unwrap();
+        board.set_piece(1, 2, Some(Player::White)).unwrap();
+        assert_eq!(board.black, 0);
+        assert_eq!(board.white, 1 << (1 + 8 * 2));
+        board.set_piece(1, 2, None).unwrap();
+        assert_eq!(board.white, 0);
     }
 
     #[test]
     fn get_piece() {
         let mut board = Board::new();
-        board.cells[3][4] = Some(Player::White);
+        board.white = 1 << (3 + 8 * 4);
         let piece = board.get_piece(3, 4).unwrap();
         assert_eq!(piece, Some(Player::White))
     }
@@ -437,6 +581,55 @@ mod tests {
         assert!(is_valid);
     }
 
+    #[test]
+    fn legal_moves_returns_all_valid_moves() {
+        let board = Board::new_start();
+        let moves: Vec<(u8, u8)> = board.legal_moves(Player::Black).collect();
+        assert_eq!(moves, vec![(3, 2), (2, 3), (5, 4), (4, 5)]);
+        let moves: Vec<(u8, u8)> = board.legal_moves(Player::White).collect();
+        assert_eq!(moves, vec![(4, 2), (5, 3), (2, 4), (3, 5)]);
+    }
+
+    #[test]
+    fn legal_moves_is_empty_when_the_player_cant_move() {
+        let mut board = Board::new();
+        board.set_piece(0, 0, Some(Player::Black)).unwrap();
+        assert_eq!(board.legal_moves(Player::Black).len(), 0);
+        assert_eq!(board.legal_moves_mask(Player::White), 0);
+    }
+
+    #[test]
+    fn legal_moves_mask_returns_valid_moves_as_bits() {
+        let board = Board::new_start();
+        let expected = 1 << (3 + 8 * 2) | 1 << (2 + 8 * 3) | 1 << (5 + 8 * 4) | 1 << (4 + 8 * 5);
+        assert_eq!(board.legal_moves_mask(Player::Black), expected);
+    }
+
+    #[test]
+    fn mobility_counts_valid_moves() {
+        let board = Board::new_start();
+        assert_eq!(board.mobility(Player::Black), 4);
+        let board = board.play(Player::Black, 4, 5).unwrap().unwrap();
+        assert_eq!(board.mobility(Player::White), 3);
+    }
+
+    #[test]
+    fn frontier_counts_pieces_next_to_empty_cells() {
+        let board = Board::from_position(
+            "XXX-----\
+             XXX-----\
+             XXX-----\
+             --------\
+             --------\
+             --------\
+             ------OO\
+             ------OO",
+        )
+        .unwrap();
+        assert_eq!(board.frontier(Player::Black), 5);
+        assert_eq!(board.frontier(Player::White), 3);
+    }
+
     #[test]
     fn play_invalid_move_if_cell_not_empty() {
         let board = Board::new_start();
@@ -470,16 +663,17 @@ mod tests {
         // * an outer square of black pieces
         // * an inner square of white pieces
         // * an empty cell a the center of both squares, at position (2, 2)
-        let mut board = Board::new();
-        for x in 0..=4 {
-            for y in 0..=4 {
-                if x == 0 || x == 4 || y == 0 || y == 4 {
-                    board.set_piece(x, y, Some(Player::Black)).unwrap()
-                } else if x != 2 || y != 2 {
-                    board.set_piece(x, y, Some(Player::White)).unwrap()
-                }
-            }
-        }
+        let board = Board::from_position(
+            "XXXXX---\
+             XOOOX---\
+             XO-OX---\
+             XOOOX---\
+             XXXXX---\
+             --------\
+             --------\
+             --------",
+        )
+        .unwrap();
 
         let result_after_move = board.play(Player::Black, 2, 2).unwrap();
         assert!(result_after_move.is_some());
@@ -499,6 +693,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_bitboards_rejects_overlapping_pieces() {
+        let board = Board::new_start();
+        let copy =
+            Board::from_bitboards(board.bitboard(Player::Black), board.bitboard(Player::White))
+                .unwrap();
+        assert_eq!(copy.to_position(), board.to_position());
+        assert!(Board::from_bitboards(0b11, 0b10).is_err());
+    }
+
     #[test]
     fn count_players_pieces() {
         let mut board = Board::new_start();
@@ -535,19 +739,168 @@ mod tests {
     }
 
     #[test]
-    fn cell_navigation() {
-        let mut cn = CellsNavigator::new((3, 3), (1, -1)).unwrap();
-        assert_eq!(cn.next(), Some((4, 2)));
-        assert_eq!(cn.next(), Some((5, 1)));
-        assert_eq!(cn.next(), Some((6, 0)));
-        assert_eq!(cn.next(), None);
+    fn coordinates_out_of_range_are_rejected() {
+        let mut board = Board::new_start();
+        assert!(board.set_piece(8, 0, Some(Player::Black)).is_err());
+        assert!(board.get_piece(0, 8).is_err());
+        assert!(board.is_move_valid(Player::Black, 8, 8).is_err());
+        assert!(board.play(Player::Black, 8, 3).is_err());
+    }
+
+    #[test]
+    fn no_capture_wraps_around_the_board() {
+        // A black piece at the end of a row, and a line of white pieces at
+        // the start of the next one : the move at the end of the line
+        // doesn't capture anything.
+        let mut board = Board::new();
+        board.set_piece(7, 0, Some(Player::Black)).unwrap();
+        board.set_piece(0, 1, Some(Player::White)).unwrap();
+        board.set_piece(1, 1, Some(Player::White)).unwrap();
+        assert!(!board.is_move_valid(Player::Black, 2, 1).unwrap());
+        assert!(board.play(Player::Black, 2, 1).unwrap().is_none());
+        assert!(!board.can_player_move(Player::Black));
+    }
+
+    #[test]
+    fn boards_are_equal_when_they_have_the_same_pieces() {
+        let board = Board::new_start()
+            .play(Player::Black, 5, 4)
+            .unwrap()
+            .unwrap();
+        let same_board = Board::from_position(&board.to_position()).unwrap();
+        assert_eq!(board, same_board);
+        assert_ne!(board, Board::new_start());
+    }
+
+    #[test]
+    fn boards_can_be_used_as_keys() {
+        use std::collections::HashSet;
+        let start = Board::new_start();
+        let mut boards = HashSet::new();
+        for (x, y) in start.legal_moves(Player::Black) {
+            let board = start.play(Player::Black, x, y).unwrap().unwrap();
+            assert!(boards.insert(board));
+            assert!(!boards.insert(Board::from_position(&board.to_position()).unwrap()));
+        }
+        assert_eq!(boards.len(), 4);
+    }
+
+    #[test]
+    fn perft_matches_the_published_counts() {
+        let counts = [1, 4, 12, 56, 244, 1396, 8200, 55092, 390_216, 3_005_288];
+        let board = Board::new_start();
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(board.perft(Player::Black, depth as u8), count);
+        }
     }
 
     #[test]
-    fn cell_navigation_reverse() {
-        let mut cn = CellsNavigator::new((3, 3), (1, -1)).unwrap();
-        assert_eq!(cn.next(), Some((4, 2)));
-        cn.reverse();
-        assert_eq!(cn.next(), Some((3, 3)));
+    fn perft_counts_passes_and_finished_games() {
+        // White can't move : he passes, then Black plays one of his moves.
+        let board = Board::from_position(&format!("XO{}", "-".repeat(62))).unwrap();
+        assert!(!board.can_player_move(Player::White));
+        assert_eq!(board.perft(Player::White, 1), 1);
+        assert_eq!(board.perft(Player::White, 2), 1);
+        // The game is over after Black's move.
+        assert_eq!(board.perft(Player::White, 5), 1);
+    }
+
+    /// Differential tests against the original array based implementation.
+    mod differential {
+        use super::super::super::array_board::ArrayBoard;
+        use super::*;
+
+        /// A tiny xorshift generator, deterministic to get reproducible tests.
+        struct TestRng(u64);
+
+        impl TestRng {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            fn below(&mut self, n: usize) -> usize {
+                (self.next() % n as u64) as usize
+            }
+        }
+
+        fn assert_same_behaviour(board: &Board, reference: &ArrayBoard) {
+            for (x, y, piece) in board.iter() {
+                assert_eq!(piece, reference.get_piece(x, y).unwrap());
+            }
+            assert_eq!(board.count_pieces(), reference.count_pieces());
+            for player in [Player::Black, Player::White].iter().copied() {
+                assert_eq!(
+                    board.can_player_move(player),
+                    reference.can_player_move(player)
+                );
+                let legal_moves: Vec<(u8, u8)> = board.legal_moves(player).collect();
+                let reference_legal_moves: Vec<(u8, u8)> = GridIterator::new()
+                    .filter(|&(x, y)| reference.is_move_valid(player, x, y).unwrap())
+                    .collect();
+                assert_eq!(legal_moves, reference_legal_moves);
+                for (x, y) in GridIterator::new() {
+                    assert_eq!(
+                        board.is_move_valid(player, x, y),
+                        reference.is_move_valid(player, x, y)
+                    );
+                    let after_move = board.play(player, x, y).unwrap();
+                    let reference_after_move = reference.play(player, x, y).unwrap();
+                    assert_eq!(after_move.is_some(), reference_after_move.is_some());
+                    if let (Some(after_move), Some(reference_after_move)) =
+                        (after_move, reference_after_move)
+                    {
+                        for (x, y, piece) in after_move.iter() {
+                            assert_eq!(piece, reference_after_move.get_piece(x, y).unwrap());
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn bitboard_behaves_as_array_board_over_random_games() {
+            let mut rng = TestRng(0x9e37_79b9_7f4a_7c15);
+            for _ in 0..50 {
+                let mut board = Board::new_start();
+                let mut reference = ArrayBoard::new_start();
+                let mut player = Player::Black;
+                loop {
+                    assert_same_behaviour(&board, &reference);
+                    if !board.can_player_move(player) {
+                        player = player.opponent();
+                        if !board.can_player_move(player) {
+                            break;
+                        }
+                    }
+                    let moves: Vec<(u8, u8)> = board.legal_moves(player).collect();
+                    let (x, y) = moves[rng.below(moves.len())];
+                    board = board.play(player, x, y).unwrap().unwrap();
+                    reference = reference.play(player, x, y).unwrap().unwrap();
+                    player = player.opponent();
+                }
+            }
+        }
+
+        #[test]
+        fn bitboard_behaves_as_array_board_over_random_positions() {
+            let mut rng = TestRng(0x2545_f491_4f6c_dd1d);
+            for _ in 0..200 {
+                let mut board = Board::new();
+                let mut reference = ArrayBoard::new();
+                for (x, y) in GridIterator::new() {
+                    let piece = match rng.below(3) {
+                        0 => None,
+                        1 => Some(Player::Black),
+                        _ => Some(Player::White),
+                    };
+                    board.set_piece(x, y, piece).unwrap();
+                    reference.set_piece(x, y, piece).unwrap();
+                }
+                assert_same_behaviour(&board, &reference);
+            }
+        }
     }
 }
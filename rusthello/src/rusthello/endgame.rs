@@ -0,0 +1,288 @@
+use std::cell::Cell;
+
+use super::board::*;
+use super::move_ordering;
+use super::time_limit::*;
+use super::virtual_player::{SearchResult, VirtualPlayer};
+
+/// Final result of a game, for a given player.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// Result of a perfect play search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// The best move, None if the player can't move.
+    pub best_move: Option<(u8, u8)>,
+    /// The final disc differential with perfect play from both sides, from
+    /// the point of view of the player to move. Empty squares are counted
+    /// for the winner.
+    pub score: i8,
+    /// The moves of both players up to the end of the game, starting with
+    /// the best move. Passes are implicit.
+    pub principal_variation: Vec<(u8, u8)>,
+}
+
+impl Solution {
+    /// Returns the outcome of the game for the player to move.
+    pub fn outcome(&self) -> Outcome {
+        match self.score {
+            score if score > 0 => Outcome::Win,
+            score if score < 0 => Outcome::Loss,
+            _ => Outcome::Draw,
+        }
+    }
+}
+
+/// Solves endgames by exploring the game tree up to its end.
+/// It's only usable when few empty squares remain, as the time needed grows
+/// exponentially with their count.
+pub struct EndgameSolver {
+    move_count: Cell<u32>,
+    clock: Clock,
+}
+
+impl Default for EndgameSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EndgameSolver {
+    /// Above this count of empty squares, moves are ordered to get more cuts.
+    /// Near the end the ordering costs more than it saves.
+    const ORDERING_MIN_EMPTIES: u8 = 7;
+
+    /// Creates a new solver.
+    pub fn new() -> Self {
+        Self {
+            move_count: Cell::new(0),
+            clock: system_clock,
+        }
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Finds the best move and the final score for the given player.
+    pub fn solve(&self, board: &Board, player: Player) -> Solution {
+        self.negamax(board, player, -64, 64)
+    }
+
+    /// Negamax implementation of alpha-beta, scores being relative to the
+    /// player to move.
+    fn negamax(&self, board: &Board, player: Player, alpha: i8, beta: i8) -> Solution {
+        let moves = Self::moves_to_explore(board, player);
+        if moves.is_empty() {
+            return if board.can_player_move(player.opponent()) {
+                let opponent_solution = self.negamax(board, player.opponent(), -beta, -alpha);
+                Solution {
+                    best_move: None,
+                    score: -opponent_solution.score,
+                    principal_variation: opponent_solution.principal_variation,
+                }
+            } else {
+                Solution {
+                    best_move: None,
+                    score: Self::final_score(board, player),
+                    principal_variation: Vec::new(),
+                }
+            };
+        }
+
+        let mut best = Solution {
+            best_move: None,
+            score: -65,
+            principal_variation: Vec::new(),
+        };
+        let mut current_alpha = alpha;
+        for (x, y) in moves {
+            let board_after_move = board
+                .play(player, x, y)
+                .expect("Unexpected error while solving endgame.")
+                .expect("Unexpected invalid move while solving endgame.");
+            self.move_count.set(self.move_count() + 1);
+
+            let opponent_solution =
+                self.negamax(&board_after_move, player.opponent(), -beta, -current_alpha);
+            if -opponent_solution.score > best.score {
+                let mut principal_variation = vec![(x, y)];
+                principal_variation.extend(opponent_solution.principal_variation);
+                best = Solution {
+                    best_move: Some((x, y)),
+                    score: -opponent_solution.score,
+                    principal_variation,
+                };
+            }
+            if best.score >= beta {
+                break;
+            }
+            current_alpha = current_alpha.max(best.score);
+        }
+
+        best
+    }
+
+    fn moves_to_explore(board: &Board, player: Player) -> Vec<(u8, u8)> {
+        let (black_pieces, white_pieces) = board.count_pieces();
+        if 64 - black_pieces - white_pieces >= Self::ORDERING_MIN_EMPTIES {
+            move_ordering::order_moves(board, player, None)
+        } else {
+            board.legal_moves(player).collect()
+        }
+    }
+
+    /// Returns the disc differential of a finished game, empty squares being
+    /// counted for the winner.
+    fn final_score(board: &Board, player: Player) -> i8 {
+        let (black_pieces, white_pieces) = board.count_pieces();
+        let (mine, theirs) = match player {
+            Player::Black => (black_pieces as i8, white_pieces as i8),
+            Player::White => (white_pieces as i8, black_pieces as i8),
+        };
+        let empties = 64 - mine - theirs;
+        match mine - theirs {
+            difference if difference > 0 => difference + empties,
+            difference if difference < 0 => difference - empties,
+            _ => 0,
+        }
+    }
+}
+
+impl VirtualPlayer for EndgameSolver {
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let start = (self.clock)();
+        let move_count = self.move_count();
+        let solution = self.solve(board, me);
+        let (black_pieces, white_pieces) = board.count_pieces();
+
+        SearchResult {
+            best_move: solution.best_move,
+            score: solution.score as i32,
+            exact: true,
+            from_book: false,
+            principal_variation: solution.principal_variation,
+            depth: 64 - black_pieces - white_pieces,
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+        }
+    }
+
+    fn move_count(&self) -> u32 {
+        self.move_count.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::Game;
+    use super::super::virtual_player::AlphaBeta;
+    use super::*;
+
+    /// Plays a game with a weak player, until the given count of empty
+    /// squares remains.
+    fn endgame(empties: u8) -> Game {
+        let mut game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = AlphaBeta::new(2);
+        loop {
+            let (black_pieces, white_pieces) = game.count_pieces();
+            if 64 - black_pieces - white_pieces <= empties {
+                return game;
+            }
+            let current = game.player().unwrap();
+            let (x, y) = player.compute_move(game.board(), current).unwrap();
+            game.play(current, x, y).unwrap();
+        }
+    }
+
+    /// Finds the score by exploring the whole game tree.
+    fn exhaustive_score(board: &Board, player: Player) -> i8 {
+        let moves: Vec<(u8, u8)> = board.legal_moves(player).collect();
+        if moves.is_empty() {
+            return if board.can_player_move(player.opponent()) {
+                -exhaustive_score(board, player.opponent())
+            } else {
+                EndgameSolver::final_score(board, player)
+            };
+        }
+
+        moves
+            .into_iter()
+            .map(|(x, y)| {
+                let board = board.play(player, x, y).unwrap().unwrap();
+                -exhaustive_score(&board, player.opponent())
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn final_score_gives_empty_squares_to_the_winner() {
+        let board =
+            Board::from_position(&format!("{}{}----", "X".repeat(40), "O".repeat(20))).unwrap();
+        assert_eq!(EndgameSolver::final_score(&board, Player::Black), 24);
+        assert_eq!(EndgameSolver::final_score(&board, Player::White), -24);
+        let board = Board::from_position(&format!("{}{}", "X".repeat(32), "O".repeat(32))).unwrap();
+        assert_eq!(EndgameSolver::final_score(&board, Player::Black), 0);
+    }
+
+    #[test]
+    fn solver_finds_the_last_move() {
+        let board = Board::from_position(&format!("-O{}", "X".repeat(62))).unwrap();
+        let solution = EndgameSolver::new().solve(&board, Player::Black);
+        assert_eq!(solution.best_move, Some((0, 0)));
+        assert_eq!(solution.score, 64);
+        assert_eq!(solution.outcome(), Outcome::Win);
+        let solution = EndgameSolver::new().solve(&board, Player::White);
+        assert_eq!(solution.best_move, None);
+        assert_eq!(solution.outcome(), Outcome::Loss);
+    }
+
+    #[test]
+    fn solver_finds_the_exact_score() {
+        let game = endgame(8);
+        let player = game.player().unwrap();
+        let solver = EndgameSolver::new();
+        let solution = solver.solve(game.board(), player);
+        assert_eq!(solution.score, exhaustive_score(game.board(), player));
+        assert!(solver.move_count() > 0);
+    }
+
+    #[test]
+    fn solver_principal_variation_ends_the_game_with_the_score() {
+        let game = endgame(8);
+        let player = game.player().unwrap();
+        let solver = EndgameSolver::new();
+        let result = solver.search(game.board(), player);
+        assert_eq!(result.depth, 8);
+        assert_eq!(result.nodes, solver.move_count());
+        let mut end = Game::from_board(*game.board(), player);
+        for &(x, y) in result.principal_variation.iter() {
+            end.play(end.player().unwrap(), x, y).unwrap();
+        }
+        assert!(end.game_over());
+        assert_eq!(
+            EndgameSolver::final_score(end.board(), player) as i32,
+            result.score
+        );
+    }
+
+    #[test]
+    fn solver_best_move_keeps_the_score() {
+        let game = endgame(10);
+        let player = game.player().unwrap();
+        let solver = EndgameSolver::new();
+        let solution = solver.solve(game.board(), player);
+        let (x, y) = solution.best_move.unwrap();
+        let board = game.board().play(player, x, y).unwrap().unwrap();
+        let next = solver.solve(&board, player.opponent());
+        assert_eq!(next.score, -solution.score);
+    }
+}
@@ -0,0 +1,250 @@
+use super::board::*;
+use super::game_status::*;
+
+/// An Evaluator is responsible for the evaluation of the state of a game,
+/// used by virtual players at the leaves of the game tree.
+pub trait Evaluator {
+    /// Returns an evaluation for the given board, when the last move was done
+    /// by the given player.
+    /// If the evaluation is ...
+    /// * positive : Black player is stronger.
+    /// * negative : White player is stronger.
+    ///
+    /// A won game should be evaluated to `i32::MAX` (or `-i32::MAX` if
+    /// White wins), to be preferred to any other position.
+    fn evaluate(&self, board: &Board, last_player: Player) -> i32;
+}
+
+/// An evaluator giving a weight to each square, with optional mobility and
+/// frontier terms.
+///
+/// The default one is rather a naive implementation : 8 for corners, 4 for
+/// other borders, 1 inside, and a bonus of 4 when the opponent can't move.
+#[derive(Debug, Copy, Clone)]
+pub struct WeightedEvaluator {
+    square_weights: [i32; 64],
+    mobility_weight: i32,
+    frontier_weight: i32,
+    opponent_blocked_weight: i32,
+}
+
+impl Default for WeightedEvaluator {
+    fn default() -> Self {
+        let mut square_weights = [0; 64];
+        for (x, y) in GridIterator::new() {
+            square_weights[(x + 8 * y) as usize] = if Self::corner(x, y) {
+                Self::SCORE_CORNER
+            } else if Self::border(x, y) {
+                Self::SCORE_BORDER
+            } else {
+                Self::SCORE_INSIDE
+            };
+        }
+
+        Self::new(square_weights).with_opponent_blocked_weight(Self::SCORE_OPPONENT_BLOCKED)
+    }
+}
+
+impl WeightedEvaluator {
+    // game is over and there is a winner.
+    const SCORE_MAX: i32 = i32::MAX;
+    // game over and no winner.
+    const SCORE_DRAW: i32 = 0;
+    // bonus if the opponent can't move the next turn.
+    const SCORE_OPPONENT_BLOCKED: i32 = 4;
+
+    // Scores according to piece position.
+    const SCORE_INSIDE: i32 = 1;
+    const SCORE_BORDER: i32 = 4;
+    const SCORE_CORNER: i32 = 8;
+
+    /// A classic table of weights, from `a1` to `h8` row by row : corners
+    /// are highly valued, the squares next to them are dangerous.
+    pub const CLASSIC_WEIGHTS: [i32; 64] = [
+        100, -20, 10, 5, 5, 10, -20, 100, //
+        -20, -50, -2, -2, -2, -2, -50, -20, //
+        10, -2, -1, -1, -1, -1, -2, 10, //
+        5, -2, -1, -1, -1, -1, -2, 5, //
+        5, -2, -1, -1, -1, -1, -2, 5, //
+        10, -2, -1, -1, -1, -1, -2, 10, //
+        -20, -50, -2, -2, -2, -2, -50, -20, //
+        100, -20, 10, 5, 5, 10, -20, 100, //
+    ];
+
+    /// Creates an evaluator with the given weights of squares, from `a1` to
+    /// `h8` row by row, without other terms.
+    pub fn new(square_weights: [i32; 64]) -> Self {
+        Self {
+            square_weights,
+            mobility_weight: 0,
+            frontier_weight: 0,
+            opponent_blocked_weight: 0,
+        }
+    }
+
+    /// Creates an evaluator using the classic weights, and favoring
+    /// mobility and few frontier pieces.
+    pub fn classic() -> Self {
+        Self::new(Self::CLASSIC_WEIGHTS)
+            .with_mobility_weight(5)
+            .with_frontier_weight(3)
+    }
+
+    /// Sets the weight of each move a player can do (the mobility).
+    pub fn with_mobility_weight(mut self, weight: i32) -> Self {
+        self.mobility_weight = weight;
+        self
+    }
+
+    /// Sets the weight of each frontier piece (pieces next to empty cells,
+    /// which give moves to the opponent). It's a penalty : the weight is
+    /// subtracted.
+    pub fn with_frontier_weight(mut self, weight: i32) -> Self {
+        self.frontier_weight = weight;
+        self
+    }
+
+    /// Sets the bonus given to a player when the opponent can't move the next
+    /// turn.
+    pub fn with_opponent_blocked_weight(mut self, weight: i32) -> Self {
+        self.opponent_blocked_weight = weight;
+        self
+    }
+
+    /// Returns the weight of the given square.
+    pub fn square_weight(&self, x: u8, y: u8) -> i32 {
+        self.square_weights[(x + 8 * y) as usize]
+    }
+
+    /// Change the sign if the given evaluation (or intermediate one) if the
+    /// player is White.
+    fn sign_for_player(player: Player, evaluation: i32) -> i32 {
+        match player {
+            Player::Black => evaluation,
+            Player::White => -evaluation,
+        }
+    }
+
+    fn corner(x: u8, y: u8) -> bool {
+        (x == 0 || x == 7) && (y == 0 || y == 7)
+    }
+
+    fn border(x: u8, y: u8) -> bool {
+        x == 0 || x == 7 || y == 0 || y == 7
+    }
+}
+
+impl Evaluator for WeightedEvaluator {
+    fn evaluate(&self, board: &Board, last_player: Player) -> i32 {
+        let status = GameStatus::evaluate_board(board);
+        if status.game_over() {
+            return match status.winner() {
+                Some(winner) => Self::sign_for_player(winner, Self::SCORE_MAX),
+                None => Self::SCORE_DRAW,
+            };
+        }
+
+        let mut evaluation = board
+            .iter()
+            .filter_map(|(x, y, piece)| {
+                piece.map(|player| Self::sign_for_player(player, self.square_weight(x, y)))
+            })
+            .sum::<i32>();
+
+        if self.mobility_weight != 0 {
+            let mobility =
+                board.mobility(Player::Black) as i32 - board.mobility(Player::White) as i32;
+            evaluation += self.mobility_weight * mobility;
+        }
+
+        if self.frontier_weight != 0 {
+            let frontier =
+                board.frontier(Player::Black) as i32 - board.frontier(Player::White) as i32;
+            evaluation -= self.frontier_weight * frontier;
+        }
+
+        if !status.can_player_move(last_player.opponent()) {
+            evaluation += Self::sign_for_player(last_player, self.opponent_blocked_weight);
+        }
+
+        evaluation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_returns_zero_for_equals_forces() {
+        let board = Board::new_start();
+        let evaluator = WeightedEvaluator::default();
+        assert_eq!(0, evaluator.evaluate(&board, Player::Black));
+    }
+
+    #[test]
+    fn evaluate_returns_positive_score_if_black_is_stronger() {
+        let board = Board::new_start();
+        let board = board.play(Player::Black, 4, 5).unwrap().unwrap();
+        let evaluator = WeightedEvaluator::default();
+        assert!(evaluator.evaluate(&board, Player::Black) > 0);
+    }
+
+    #[test]
+    fn evaluate_returns_negative_score_if_white_is_stronger() {
+        let board = Board::from_position(
+            "--------\
+             --------\
+             --------\
+             ---OX---\
+             ---OO---\
+             --------\
+             --------\
+             --------",
+        )
+        .unwrap();
+        let evaluator = WeightedEvaluator::default();
+        assert!(evaluator.evaluate(&board, Player::Black) < 0);
+    }
+
+    #[test]
+    fn default_weights_favor_corners_and_borders() {
+        let evaluator = WeightedEvaluator::default();
+        assert_eq!(evaluator.square_weight(0, 0), 8);
+        assert_eq!(evaluator.square_weight(7, 3), 4);
+        assert_eq!(evaluator.square_weight(3, 3), 1);
+    }
+
+    #[test]
+    fn evaluate_uses_the_square_weights() {
+        let mut square_weights = [0; 64];
+        square_weights[0] = 10;
+        let evaluator = WeightedEvaluator::new(square_weights);
+        let mut board = Board::new_start();
+        board.set_piece(0, 0, Some(Player::White)).unwrap();
+        assert_eq!(evaluator.evaluate(&board, Player::Black), -10);
+    }
+
+    #[test]
+    fn evaluate_uses_mobility_and_frontier() {
+        let board = Board::new_start()
+            .play(Player::Black, 4, 5)
+            .unwrap()
+            .unwrap();
+        let mobility = WeightedEvaluator::new([0; 64]).with_mobility_weight(1);
+        // Black can move at 3 positions, White too.
+        assert_eq!(mobility.evaluate(&board, Player::Black), 0);
+        let frontier = WeightedEvaluator::new([0; 64]).with_frontier_weight(1);
+        // All pieces are frontier pieces : 4 black and 1 white.
+        assert_eq!(frontier.evaluate(&board, Player::Black), -3);
+    }
+
+    #[test]
+    fn evaluate_won_games_to_the_maximum() {
+        let board = Board::from_position(&"X".repeat(64)).unwrap();
+        let evaluator = WeightedEvaluator::classic();
+        assert_eq!(evaluator.evaluate(&board, Player::Black), i32::MAX);
+        let board = Board::from_position(&"O".repeat(64)).unwrap();
+        assert_eq!(evaluator.evaluate(&board, Player::Black), -i32::MAX);
+    }
+}
@@ -1,33 +1,130 @@
 use super::board::*;
 use super::game_status::*;
+
+/// A move recorded in the history of a game.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Move {
+    /// The player put a piece at the given coordinates.
+    Play { player: Player, x: u8, y: u8 },
+    /// The player can't move and passes his turn.
+    Pass { player: Player },
+}
+
+impl Move {
+    /// Returns the player who did the move.
+    pub fn player(&self) -> Player {
+        match *self {
+            Move::Play { player, .. } => player,
+            Move::Pass { player } => player,
+        }
+    }
+
+    fn is_pass(&self) -> bool {
+        matches!(self, Move::Pass { .. })
+    }
+}
+
 /// Manage an Othello game workflow
 pub struct Game {
     board: Board,
     player: Option<Player>,
     opponent_is_blocked: bool,
     status: GameStatus,
+    // Starting position, used to replay the history.
+    start_board: Board,
+    start_player: Player,
+    // All recorded moves, including the undone ones that can be redone.
+    moves: Vec<Move>,
+    // Count of moves in `moves` leading to the current position.
+    current: usize,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
     /// Create a new standard game
     pub fn new() -> Game {
-        let board = Board::new_start();
+        Self::from_board(Board::new_start(), Player::Black)
+    }
+
+    /// Create a game starting from any position, the given player being the
+    /// next to move. If he can't move, he passes and it's recorded in the
+    /// history.
+    pub fn from_board(board: Board, player: Player) -> Game {
         let mut game = Game {
-            board: board,
-            player: Some(Player::Black),
+            board,
+            player: Some(player),
             opponent_is_blocked: false,
             status: Default::default(),
+            start_board: board,
+            start_player: player,
+            moves: Vec::new(),
+            current: 0,
         };
-        game.update_status();
+        game.restart();
+        if game.player == Some(player.opponent()) {
+            game.moves.push(Move::Pass { player });
+            game.current = game.moves.len();
+        }
 
         game
     }
 
+    /// Goes back to the starting position, without forgetting the history.
+    fn restart(&mut self) {
+        self.board = self.start_board;
+        self.opponent_is_blocked = false;
+        self.update_status();
+        self.player = if self.game_over() {
+            None
+        } else if self.status.can_player_move(self.start_player) {
+            Some(self.start_player)
+        } else {
+            Some(self.start_player.opponent())
+        };
+    }
+
     pub fn board(&self) -> &Board {
         &self.board
     }
 
+    /// Plays a move for the given player and records it in the history.
+    /// Moves which could have been redone are forgotten.
     pub fn play(&mut self, player: Player, x: u8, y: u8) -> Result<(), String> {
+        self.apply_move(player, x, y)?;
+
+        self.moves.truncate(self.current);
+        self.moves.push(Move::Play { player, x, y });
+        if self.opponent_is_blocked {
+            self.moves.push(Move::Pass {
+                player: player.opponent(),
+            });
+        }
+        self.current = self.moves.len();
+
+        Ok(())
+    }
+
+    /// Checks that the given player passed just before the current
+    /// position, ie to read records of games where passes are explicit : as
+    /// passes are played automatically when a player can't move, there is
+    /// nothing to play.
+    pub fn check_pass(&self, player: Player) -> Result<(), String> {
+        match self.player {
+            _ if self.last_pass() == Some(player) => Ok(()),
+            Some(next_player) if next_player == player => {
+                Err(format!("{} can move, he can't pass.", player))
+            }
+            _ => Err(format!("{} didn't have to pass.", player)),
+        }
+    }
+
+    /// Plays a move without recording it.
+    fn apply_move(&mut self, player: Player, x: u8, y: u8) -> Result<(), String> {
         match self.player {
             None => return Err("None of the players can move, the game is over.".to_string()),
             Some(p) if p != player => {
@@ -46,6 +143,133 @@ impl Game {
         }
     }
 
+    /// Returns the moves leading to the current position, passes included.
+    pub fn history(&self) -> &[Move] {
+        &self.moves[..self.current]
+    }
+
+    /// Returns the player who passed just before the current position, as
+    /// he couldn't move, if any.
+    pub fn last_pass(&self) -> Option<Player> {
+        match self.history().last() {
+            Some(Move::Pass { player }) => Some(*player),
+            _ => None,
+        }
+    }
+
+    /// Returns the position the game started from, and the player who was
+    /// to move.
+    pub fn start_position(&self) -> (&Board, Player) {
+        (&self.start_board, self.start_player)
+    }
+
+    /// Returns the moves of the next player, in grid order.
+    pub fn legal_moves(&self) -> Vec<(u8, u8)> {
+        match self.player {
+            Some(player) => self.board.legal_moves(player).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the count of pieces played to reach the current position
+    /// (passes are not counted).
+    pub fn ply(&self) -> usize {
+        Self::count_plies(self.history())
+    }
+
+    /// Returns the count of pieces played in the whole recorded history,
+    /// including the moves which could be redone.
+    pub fn last_ply(&self) -> usize {
+        Self::count_plies(&self.moves)
+    }
+
+    fn count_plies(moves: &[Move]) -> usize {
+        moves.iter().filter(|m| !m.is_pass()).count()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.ply() > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.ply() < self.last_ply()
+    }
+
+    /// Takes back the last move played, and the passes which followed it.
+    pub fn undo(&mut self) -> Result<(), String> {
+        if !self.can_undo() {
+            return Err("There is no move to undo.".to_string());
+        }
+        self.go_to_ply(self.ply() - 1)
+    }
+
+    /// Takes back moves until it's the turn of the given player, at least
+    /// one move being taken back. It's useful to take back a move against a
+    /// virtual player, whose reply is taken back too. The game is unchanged
+    /// when the player has no move to take back.
+    pub fn undo_for(&mut self, player: Player) -> Result<(), String> {
+        let ply = self.ply();
+        loop {
+            if self.undo().is_err() {
+                self.go_to_ply(ply)?;
+                return Err(format!("{} has no move to undo.", player));
+            }
+            if self.player == Some(player) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Plays again the last move taken back.
+    pub fn redo(&mut self) -> Result<(), String> {
+        if !self.can_redo() {
+            return Err("There is no move to redo.".to_string());
+        }
+        self.go_to_ply(self.ply() + 1)
+    }
+
+    /// Goes to the position reached after the given count of pieces played,
+    /// backward or forward in the recorded history.
+    /// Passes are forced moves, then they're always played after a move.
+    pub fn go_to_ply(&mut self, ply: usize) -> Result<(), String> {
+        if ply > self.last_ply() {
+            return Err(format!(
+                "The ply {} is out of the history (last ply : {}).",
+                ply,
+                self.last_ply()
+            ));
+        }
+
+        // Finds the count of moves to replay, skipping the passes following
+        // the last piece played.
+        let mut count = 0;
+        let mut plies = 0;
+        while plies < ply {
+            if !self.moves[count].is_pass() {
+                plies += 1;
+            }
+            count += 1;
+        }
+        while count < self.moves.len() && self.moves[count].is_pass() {
+            count += 1;
+        }
+
+        self.replay(count)
+    }
+
+    /// Replays the given count of recorded moves from the starting position.
+    fn replay(&mut self, count: usize) -> Result<(), String> {
+        self.restart();
+        for index in 0..count {
+            if let Move::Play { player, x, y } = self.moves[index] {
+                self.apply_move(player, x, y)?;
+            }
+        }
+        self.current = count;
+
+        Ok(())
+    }
+
     fn update_status(&mut self) {
         self.status = GameStatus::evaluate_board(&self.board);
     }
@@ -53,6 +277,7 @@ impl Game {
     fn update_player(&mut self) {
         if self.game_over() {
             self.player = None;
+            self.opponent_is_blocked = false;
             return;
         }
 
@@ -87,6 +312,15 @@ impl Game {
         self.status.winner()
     }
 
+    /// Counts the positions reached from the current one after the given
+    /// count of plies, see `Board::perft`.
+    pub fn perft(&self, depth: u8) -> u64 {
+        match self.player {
+            Some(player) => self.board.perft(player, depth),
+            None => 1,
+        }
+    }
+
     pub fn count_pieces(&self) -> (u8, u8) {
         (
             self.status.pieces_count(Player::Black),
@@ -160,9 +394,269 @@ mod tests {
         assert_eq!(game.winner(), Some(Player::Black));
     }
 
+    #[test]
+    fn new_game_has_an_empty_history() {
+        let game = Game::new();
+        assert!(game.history().is_empty());
+        assert_eq!(game.ply(), 0);
+        assert!(!game.can_undo());
+        assert!(!game.can_redo());
+    }
+
+    #[test]
+    fn play_records_moves_in_history() {
+        let mut game = Game::new();
+        game.play(Player::Black, 4, 5).unwrap();
+        game.play(Player::White, 5, 5).unwrap();
+        assert_eq!(
+            game.history(),
+            &[
+                Move::Play {
+                    player: Player::Black,
+                    x: 4,
+                    y: 5
+                },
+                Move::Play {
+                    player: Player::White,
+                    x: 5,
+                    y: 5
+                },
+            ]
+        );
+        assert_eq!(game.ply(), 2);
+    }
+
+    #[test]
+    fn invalid_moves_are_not_recorded() {
+        let mut game = Game::new();
+        assert!(game.play(Player::Black, 0, 0).is_err());
+        assert!(game.play(Player::White, 4, 5).is_err());
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn undo_restores_previous_position() {
+        let mut game = Game::new();
+        game.play(Player::Black, 4, 5).unwrap();
+        game.play(Player::White, 5, 5).unwrap();
+        game.undo().unwrap();
+        assert_eq!(game.ply(), 1);
+        assert_eq!(game.player(), Some(Player::White));
+        assert_eq!(game.count_pieces(), (4, 1));
+        game.undo().unwrap();
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(game.count_pieces(), (2, 2));
+        assert!(game.undo().is_err());
+    }
+
+    #[test]
+    fn redo_plays_again_undone_moves() {
+        let mut game = Game::new();
+        game.play(Player::Black, 4, 5).unwrap();
+        game.play(Player::White, 5, 5).unwrap();
+        game.go_to_ply(0).unwrap();
+        assert!(game.can_redo());
+        game.redo().unwrap();
+        assert_eq!(game.count_pieces(), (4, 1));
+        game.redo().unwrap();
+        assert_eq!(game.count_pieces(), (3, 3));
+        assert_eq!(game.player(), Some(Player::Black));
+        assert!(game.redo().is_err());
+    }
+
+    #[test]
+    fn play_after_undo_forgets_undone_moves() {
+        let mut game = Game::new();
+        game.play(Player::Black, 4, 5).unwrap();
+        game.play(Player::White, 5, 5).unwrap();
+        game.undo().unwrap();
+        game.play(Player::White, 3, 5).unwrap();
+        assert!(!game.can_redo());
+        assert_eq!(game.last_ply(), 2);
+        assert_eq!(
+            game.history()[1],
+            Move::Play {
+                player: Player::White,
+                x: 3,
+                y: 5
+            }
+        );
+    }
+
+    #[test]
+    fn go_to_ply_rejects_unknown_plies() {
+        let mut game = Game::new();
+        game.play(Player::Black, 4, 5).unwrap();
+        assert!(game.go_to_ply(2).is_err());
+        assert_eq!(game.ply(), 1);
+    }
+
+    /// Plays a short game where black wins after 12 moves, white being
+    /// blocked after the 11th one.
+    fn play_game_with_a_pass(game: &mut Game) {
+        let moves = [
+            (Player::Black, 3, 2),
+            (Player::White, 2, 4),
+            (Player::Black, 1, 5),
+            (Player::White, 5, 2),
+            (Player::Black, 5, 4),
+            (Player::White, 2, 5),
+            (Player::Black, 2, 6),
+            (Player::White, 2, 1),
+            (Player::Black, 1, 0),
+            (Player::White, 1, 6),
+            (Player::Black, 0, 7),
+            (Player::Black, 6, 1),
+        ];
+        for (player, x, y) in moves.iter() {
+            game.play(*player, *x, *y).unwrap();
+        }
+    }
+
+    #[test]
+    fn history_records_passes() {
+        let mut game = Game::new();
+        play_game_with_a_pass(&mut game);
+        let history = game.history();
+        assert_eq!(history.len(), 13);
+        assert_eq!(
+            history[11],
+            Move::Pass {
+                player: Player::White
+            }
+        );
+        assert_eq!(game.ply(), 12);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_blocked_opponent_and_game_status() {
+        let mut game = Game::new();
+        play_game_with_a_pass(&mut game);
+        assert!(game.game_over());
+        game.undo().unwrap();
+        assert!(!game.game_over());
+        assert!(game.opponent_is_blocked());
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(game.history().len(), 12);
+        game.undo().unwrap();
+        assert!(!game.opponent_is_blocked());
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(game.history().len(), 10);
+        game.redo().unwrap();
+        assert!(game.opponent_is_blocked());
+        assert_eq!(game.history().len(), 12);
+        game.redo().unwrap();
+        assert!(game.game_over());
+        assert_eq!(game.player(), None);
+        assert_eq!(game.winner(), Some(Player::Black));
+    }
+
+    #[test]
+    fn go_to_ply_moves_backward_and_forward() {
+        let mut game = Game::new();
+        play_game_with_a_pass(&mut game);
+        game.go_to_ply(3).unwrap();
+        assert_eq!(game.history().len(), 3);
+        assert_eq!(game.player(), Some(Player::White));
+        game.go_to_ply(11).unwrap();
+        assert_eq!(game.history().len(), 12);
+        assert_eq!(game.player(), Some(Player::Black));
+        game.go_to_ply(game.last_ply()).unwrap();
+        assert!(game.game_over());
+    }
+
+    #[test]
+    fn from_board_starts_with_the_given_player() {
+        let board = Board::new_start()
+            .play(Player::Black, 4, 5)
+            .unwrap()
+            .unwrap();
+        let game = Game::from_board(board, Player::White);
+        assert_eq!(game.player(), Some(Player::White));
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn from_board_records_a_pass_if_the_player_cant_move() {
+        let mut board = Board::new();
+        board.set_piece(0, 0, Some(Player::Black)).unwrap();
+        board.set_piece(1, 0, Some(Player::White)).unwrap();
+        let mut game = Game::from_board(board, Player::White);
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(
+            game.history(),
+            &[Move::Pass {
+                player: Player::White
+            }]
+        );
+        game.play(Player::Black, 2, 0).unwrap();
+        assert!(game.game_over());
+        game.undo().unwrap();
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(game.history().len(), 1);
+    }
+
     #[test]
     fn count_pieces() {
         let game = Game::new();
         assert_eq!(game.count_pieces(), (2, 2));
     }
+
+    #[test]
+    fn perft_starts_from_the_current_position() {
+        assert_eq!(Game::new().perft(3), 56);
+        // The 4 first moves are symmetric.
+        let mut game = Game::new();
+        game.play(Player::Black, 5, 4).unwrap();
+        assert_eq!(game.perft(2), 56 / 4);
+    }
+
+    #[test]
+    fn undo_for_takes_back_the_reply_of_the_opponent() {
+        let mut game = Game::new();
+        game.play(Player::Black, 5, 4).unwrap();
+        game.play(Player::White, 3, 5).unwrap();
+        game.play(Player::Black, 2, 2).unwrap();
+        game.undo_for(Player::White).unwrap();
+        assert_eq!(game.ply(), 1);
+        assert_eq!(game.player(), Some(Player::White));
+        game.undo_for(Player::Black).unwrap();
+        assert_eq!(game.ply(), 0);
+        game.play(Player::Black, 5, 4).unwrap();
+        assert!(game.undo_for(Player::White).is_err());
+        assert_eq!(game.ply(), 1);
+    }
+
+    #[test]
+    fn passes_are_checked_and_reported() {
+        let mut board = Board::new();
+        board.set_piece(0, 0, Some(Player::Black)).unwrap();
+        board.set_piece(1, 0, Some(Player::White)).unwrap();
+        board.set_piece(3, 0, Some(Player::White)).unwrap();
+        let mut game = Game::from_board(board, Player::White);
+        assert_eq!(game.last_pass(), Some(Player::White));
+        assert!(game.check_pass(Player::White).is_ok());
+        assert_eq!(
+            game.check_pass(Player::Black),
+            Err("Black can move, he can't pass.".to_string())
+        );
+        assert_eq!(game.legal_moves(), vec![(2, 0)]);
+        game.play(Player::Black, 2, 0).unwrap();
+        // White still can't move.
+        assert_eq!(game.last_pass(), Some(Player::White));
+        assert_eq!(game.legal_moves(), vec![(4, 0)]);
+        game.play(Player::Black, 4, 0).unwrap();
+        // The game is over, nobody passes.
+        assert!(game.game_over());
+        assert_eq!(game.last_pass(), None);
+        assert_eq!(
+            game.check_pass(Player::Black),
+            Err("Black didn't have to pass.".to_string())
+        );
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(game.start_position(), (&board, Player::White));
+        let game = Game::from_transcript("f5").unwrap();
+        assert_eq!(game.last_pass(), None);
+        assert!(game.check_pass(Player::Black).is_err());
+    }
 }
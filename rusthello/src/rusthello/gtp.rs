@@ -0,0 +1,295 @@
+use std::{
+    cmp::Ordering,
+    io::{self, BufRead, Write},
+};
+
+use super::super::ascii_board::board_to_ascii;
+use super::board::*;
+use super::game::*;
+use super::notation::*;
+use super::virtual_player::VirtualPlayer;
+
+/// The commands known by the GTP engine.
+const COMMANDS: [&str; 16] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "clear_board",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "final_score",
+    "loadpos",
+    "position",
+    "legal_moves",
+    "is_game_over",
+];
+
+/// An engine speaking a dialect of the Go Text Protocol adapted to Othello,
+/// to drive games from scripts. A command is a line, optionally prefixed by
+/// a numeric id, ie `12 play black f5`. The answer starts with `=` on
+/// success or `?` on failure, followed by the id if any and the result, and
+/// ends with an empty line.
+///
+/// Moves are squares (ie `f5`) or `pass`, colors are `black` or `white`
+/// (`b` and `w` being accepted), and positions are given as by
+/// `Game::to_position`.
+pub struct GtpEngine {
+    game: Game,
+    player: Box<dyn VirtualPlayer>,
+}
+
+impl GtpEngine {
+    /// Creates an engine choosing its moves with the given virtual player.
+    pub fn new(player: Box<dyn VirtualPlayer>) -> Self {
+        Self {
+            game: Game::new(),
+            player,
+        }
+    }
+
+    /// Returns the game known by the engine.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Reads commands until `quit` or the end of the input, writing the
+    /// answers as soon as they're known.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (id, command) = match line.find(' ') {
+                Some(index) if line[..index].chars().all(|c| c.is_ascii_digit()) => {
+                    (&line[..index], line[index + 1..].trim())
+                }
+                _ if line.chars().all(|c| c.is_ascii_digit()) => (line, ""),
+                _ => ("", line),
+            };
+            let answer = match self.execute(command) {
+                Ok(result) => format!("={} {}", id, result),
+                Err(message) => format!("?{} {}", id, message),
+            };
+            writeln!(output, "{}\n", answer.trim_end())?;
+            output.flush()?;
+            if command == "quit" {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes a command without id, returning its result or an error
+    /// message.
+    pub fn execute(&mut self, command: &str) -> Result<String, String> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let arguments: Vec<&str> = words.collect();
+
+        match (name, arguments.as_slice()) {
+            ("protocol_version", []) => Ok("2".to_string()),
+            ("name", []) => Ok("Rusthello".to_string()),
+            ("version", []) => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            ("known_command", [command]) => Ok(COMMANDS.contains(command).to_string()),
+            ("list_commands", []) => Ok(COMMANDS.join("\n")),
+            ("quit", []) => Ok(String::new()),
+            ("clear_board", []) => {
+                self.game = Game::new();
+                Ok(String::new())
+            }
+            ("play", [color, square]) => {
+                let player = parse_color(color)?;
+                if square.eq_ignore_ascii_case("pass") {
+                    self.game.check_pass(player)?;
+                } else {
+                    let (x, y) = parse_square(square)?;
+                    self.game.play(player, x, y)?;
+                }
+                Ok(String::new())
+            }
+            ("genmove", [color]) => self.generate_move(parse_color(color)?),
+            ("undo", []) => {
+                self.game.undo()?;
+                Ok(String::new())
+            }
+            ("showboard", []) => Ok(format!("\n{}", board_to_ascii(self.game.board()))),
+            ("final_score", []) => Ok(self.score()),
+            ("loadpos", _) if !arguments.is_empty() => {
+                self.game = Game::from_position(&arguments.concat())?;
+                Ok(String::new())
+            }
+            ("position", []) => Ok(self.game.to_position()),
+            ("legal_moves", [color]) => {
+                let moves: Vec<String> = self
+                    .game
+                    .board()
+                    .legal_moves(parse_color(color)?)
+                    .map(|(x, y)| square_to_string(x, y))
+                    .collect();
+                Ok(moves.join(" "))
+            }
+            ("is_game_over", []) => Ok(self.game.game_over().to_string()),
+            _ if COMMANDS.contains(&name) => Err("syntax error".to_string()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    /// Plays the move of the virtual player for the given color, and returns
+    /// it. The engine passes if the player couldn't move.
+    fn generate_move(&mut self, player: Player) -> Result<String, String> {
+        match self.game.player() {
+            None => return Err("the game is over".to_string()),
+            Some(next_player) if next_player != player => {
+                return match self.game.check_pass(player) {
+                    Ok(()) => Ok("pass".to_string()),
+                    Err(_) => Err(format!("it's the turn of {}", next_player)),
+                };
+            }
+            _ => (),
+        }
+        let (x, y) = self
+            .player
+            .compute_move(self.game.board(), player)
+            .ok_or_else(|| "no move found".to_string())?;
+        self.game.play(player, x, y)?;
+
+        Ok(square_to_string(x, y))
+    }
+
+    /// Returns the score as the difference of discs, ie `B+10`, `W+2` or `0`.
+    fn score(&self) -> String {
+        let (black, white) = self.game.count_pieces();
+        match black.cmp(&white) {
+            Ordering::Greater => format!("B+{}", black - white),
+            Ordering::Less => format!("W+{}", white - black),
+            Ordering::Equal => "0".to_string(),
+        }
+    }
+}
+
+/// Parses a color, ie `black`, `b`, `white` or `w`, whatever the case.
+fn parse_color(color: &str) -> Result<Player, String> {
+    match color.to_ascii_lowercase().as_str() {
+        "black" | "b" => Ok(Player::Black),
+        "white" | "w" => Ok(Player::White),
+        _ => Err(format!("invalid color '{}'", color)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::run;
+    use super::super::virtual_player::AlphaBeta;
+    use super::*;
+
+    fn new_engine() -> GtpEngine {
+        GtpEngine::new(Box::new(AlphaBeta::new(2)))
+    }
+
+    #[test]
+    fn answers_start_with_the_status_and_the_id() {
+        let mut engine = new_engine();
+        let output = run(
+            &mut engine,
+            "protocol_version\n3 name\n# comment\n\n4 unknown\nplay black a1\n",
+        );
+        assert_eq!(
+            output,
+            "= 2\n\n=3 Rusthello\n\n?4 unknown command\n\n? The move is invalid.\n\n"
+        );
+    }
+
+    #[test]
+    fn play_and_undo_moves() {
+        let mut engine = new_engine();
+        let output = run(&mut engine, "play b f5\nplay w d6\nundo\nplay black c3\n");
+        assert_eq!(
+            output,
+            "=\n\n=\n\n=\n\n? It's the turn of White, not Black.\n\n"
+        );
+        assert_eq!(engine.game().to_transcript(), Ok("f5".to_string()));
+    }
+
+    #[test]
+    fn genmove_plays_the_move_of_the_virtual_player() {
+        let mut engine = new_engine();
+        let output = run(&mut engine, "genmove black\ngenmove white\n");
+        let moves: Vec<&str> = output
+            .split("\n\n")
+            .filter(|answer| !answer.is_empty())
+            .map(|answer| answer.strip_prefix("= ").unwrap())
+            .collect();
+        assert_eq!(Ok(moves.concat()), engine.game().to_transcript());
+        assert_eq!(moves.len(), 2);
+    }
+
+    #[test]
+    fn genmove_rejects_the_player_who_isnt_to_move() {
+        let mut engine = new_engine();
+        assert_eq!(
+            engine.execute("genmove white"),
+            Err("it's the turn of Black".to_string())
+        );
+        assert_eq!(engine.game().to_transcript(), Ok("".to_string()));
+    }
+
+    #[test]
+    fn passes_are_played_and_generated() {
+        let mut engine = new_engine();
+        let position = format!("XO{} O", "-".repeat(62));
+        assert_eq!(
+            run(&mut engine, &format!("loadpos {}\n", position)),
+            "=\n\n"
+        );
+        assert_eq!(engine.execute("genmove white"), Ok("pass".to_string()));
+        assert!(engine.execute("play black pass").is_err());
+        assert_eq!(engine.execute("play white pass"), Ok(String::new()));
+        assert_eq!(engine.execute("is_game_over"), Ok("false".to_string()));
+        assert_eq!(engine.execute("legal_moves black"), Ok("c1".to_string()));
+        assert_eq!(engine.execute("genmove b"), Ok("c1".to_string()));
+        assert_eq!(engine.execute("is_game_over"), Ok("true".to_string()));
+        assert_eq!(engine.execute("final_score"), Ok("B+3".to_string()));
+        assert!(engine.execute("genmove white").is_err());
+    }
+
+    #[test]
+    fn showboard_and_position_describe_the_game() {
+        let mut engine = new_engine();
+        let output = run(&mut engine, "showboard\n");
+        assert!(output.starts_with("= \n    A   B"));
+        assert!(output.ends_with("+---+\n\n"));
+        assert_eq!(engine.execute("position"), Ok(Game::new().to_position()));
+        assert_eq!(engine.execute("final_score"), Ok("0".to_string()));
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        let mut engine = new_engine();
+        assert_eq!(
+            engine.execute("play red f5"),
+            Err("invalid color 'red'".to_string())
+        );
+        assert_eq!(
+            engine.execute("play black"),
+            Err("syntax error".to_string())
+        );
+        assert!(engine.execute("loadpos XO").is_err());
+        assert_eq!(
+            engine.execute("known_command genmove"),
+            Ok("true".to_string())
+        );
+        assert_eq!(engine.execute("known_command go"), Ok("false".to_string()));
+        assert_eq!(
+            engine.execute("undo"),
+            Err("There is no move to undo.".to_string())
+        );
+    }
+}
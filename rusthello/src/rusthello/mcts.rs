@@ -0,0 +1,344 @@
+use std::cell::{Cell, RefCell};
+
+use super::board::*;
+use super::random::Random;
+use super::search::next_player;
+use super::time_limit::*;
+use super::virtual_player::{SearchResult, VirtualPlayer};
+
+/// Implementation of the Monte Carlo Tree Search algorithm (UCT).
+/// Positions aren't evaluated, moves are chosen according to the results of
+/// random games (playouts), the most promising moves being explored more.
+///
+/// Scores are the estimated chances to win, from -100 (sure loss) to 100
+/// (sure win).
+pub struct Mcts {
+    playouts: u32,
+    time_limit: Option<TimeLimit>,
+    exploration: f64,
+    random: RefCell<Random>,
+    move_count: Cell<u32>,
+    clock: Clock,
+}
+
+impl Mcts {
+    /// The default seed of the random generator.
+    const DEFAULT_SEED: u64 = 0x0123_4567_89ab_cdef;
+
+    /// Creates a new MCTS, fixing its count of playouts per move.
+    pub fn new(playouts: u32) -> Self {
+        Self {
+            playouts,
+            time_limit: None,
+            exploration: std::f64::consts::SQRT_2,
+            random: RefCell::new(Random::new(Self::DEFAULT_SEED)),
+            move_count: Cell::new(0),
+            clock: system_clock,
+        }
+    }
+
+    /// Sets a time budget for each move, the count of playouts given at
+    /// creation becoming the maximum count.
+    /// The search time is then measured with the clock of the time limit.
+    pub fn with_time_limit(mut self, time_limit: TimeLimit) -> Self {
+        self.time_limit = Some(time_limit);
+        self.clock = time_limit.clock();
+        self
+    }
+
+    /// Sets the seed of the random generator, the same seed giving the same
+    /// moves.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = RefCell::new(Random::new(seed));
+        self
+    }
+
+    /// Sets the exploration constant of UCT (square root of 2 by default) :
+    /// the greater, the more less promising moves are explored.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builds the tree of explored positions.
+    fn build_tree(&self, board: &Board, me: Player) -> Vec<Node> {
+        let mut tree = vec![Node::new(*board, me.opponent(), None, None)];
+        let deadline = self.time_limit.map(|time_limit| time_limit.start());
+        let mut playouts = 0;
+        while playouts < self.playouts.max(1) {
+            // The first playout is always done, to always find a move.
+            if playouts > 0 && deadline.is_some_and(|deadline| deadline.expired()) {
+                break;
+            }
+
+            let node = self.select(&tree);
+            let node = self.expand(&mut tree, node);
+            let winner = self.playout(&tree[node].board, tree[node].player);
+            Self::backpropagate(&mut tree, node, winner);
+            playouts += 1;
+        }
+
+        tree
+    }
+
+    /// Goes down the tree, choosing the most promising children, up to a
+    /// node having moves not explored yet (or ending the game).
+    fn select(&self, tree: &[Node]) -> usize {
+        let mut node = 0;
+        while tree[node].untried_moves.is_empty() && !tree[node].children.is_empty() {
+            let parent_visits = tree[node].visits as f64;
+            node = *tree[node]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let uct_a = tree[a].uct(parent_visits, self.exploration);
+                    let uct_b = tree[b].uct(parent_visits, self.exploration);
+                    uct_a.partial_cmp(&uct_b).unwrap()
+                })
+                .unwrap();
+        }
+
+        node
+    }
+
+    /// Adds to the tree a child of the given node, for a random move not
+    /// explored yet. Returns the new node, or the given one if all its moves
+    /// are explored.
+    fn expand(&self, tree: &mut Vec<Node>, node: usize) -> usize {
+        if tree[node].untried_moves.is_empty() {
+            return node;
+        }
+
+        let index = self
+            .random
+            .borrow_mut()
+            .below(tree[node].untried_moves.len());
+        let (x, y) = tree[node].untried_moves.swap_remove(index);
+        let player = tree[node].player.unwrap();
+        let board = tree[node]
+            .board
+            .play(player, x, y)
+            .expect("Unexpected error while expanding the tree.")
+            .expect("Unexpected invalid move while expanding the tree.");
+        self.move_count.set(self.move_count() + 1);
+
+        let child = tree.len();
+        tree.push(Node::new(board, player, Some((x, y)), Some(node)));
+        tree[node].children.push(child);
+        child
+    }
+
+    /// Plays random moves up to the end of the game, and returns the winner.
+    fn playout(&self, board: &Board, player: Option<Player>) -> Option<Player> {
+        let mut board = *board;
+        let mut player = player;
+        let mut random = self.random.borrow_mut();
+        while let Some(current_player) = player {
+            let moves: Vec<(u8, u8)> = board.legal_moves(current_player).collect();
+            let (x, y) = moves[random.below(moves.len())];
+            board = board
+                .play(current_player, x, y)
+                .expect("Unexpected error while playing randomly.")
+                .expect("Unexpected invalid move while playing randomly.");
+            self.move_count.set(self.move_count() + 1);
+            player = next_player(&board, current_player);
+        }
+
+        let (black_pieces, white_pieces) = board.count_pieces();
+        match black_pieces.cmp(&white_pieces) {
+            std::cmp::Ordering::Greater => Some(Player::Black),
+            std::cmp::Ordering::Less => Some(Player::White),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Records the result of a playout from the given node up to the root.
+    fn backpropagate(tree: &mut [Node], node: usize, winner: Option<Player>) {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            tree[node].visits += 1;
+            tree[node].wins += match winner {
+                Some(winner) if winner == tree[node].last_player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            current = tree[node].parent;
+        }
+    }
+
+    /// Returns the most visited child of a node.
+    fn most_visited_child(tree: &[Node], node: usize) -> Option<usize> {
+        tree[node]
+            .children
+            .iter()
+            .copied()
+            .fold(None, |best: Option<usize>, child| match best {
+                Some(best) if tree[best].visits >= tree[child].visits => Some(best),
+                _ => Some(child),
+            })
+    }
+}
+
+/// A position of the tree, and the statistics of the playouts done from it.
+struct Node {
+    board: Board,
+    // the player to move, None when the game is over.
+    player: Option<Player>,
+    // the player who did the move leading to the position.
+    last_player: Player,
+    last_move: Option<(u8, u8)>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried_moves: Vec<(u8, u8)>,
+    visits: u32,
+    // sum of the results for the last player : 1 for a win, 0.5 for a draw.
+    wins: f64,
+}
+
+impl Node {
+    fn new(
+        board: Board,
+        last_player: Player,
+        last_move: Option<(u8, u8)>,
+        parent: Option<usize>,
+    ) -> Self {
+        let player = next_player(&board, last_player);
+        let untried_moves = match player {
+            Some(player) => board.legal_moves(player).collect(),
+            None => Vec::new(),
+        };
+        Self {
+            board,
+            player,
+            last_player,
+            last_move,
+            parent,
+            children: Vec::new(),
+            untried_moves,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    /// Returns the share of playouts won by the last player.
+    fn win_rate(&self) -> f64 {
+        self.wins / self.visits as f64
+    }
+
+    /// Returns the UCT value of the node, the greater the more promising.
+    fn uct(&self, parent_visits: f64, exploration: f64) -> f64 {
+        self.win_rate() + exploration * (parent_visits.ln() / self.visits as f64).sqrt()
+    }
+}
+
+impl VirtualPlayer for Mcts {
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let start = (self.clock)();
+        let move_count = self.move_count();
+        let tree = self.build_tree(board, me);
+
+        let mut principal_variation = Vec::new();
+        let mut node = 0;
+        while let Some(child) = Self::most_visited_child(&tree, node) {
+            principal_variation.push(tree[child].last_move.unwrap());
+            node = child;
+        }
+
+        // The chances to win of the player are the ones of the best move, or
+        // the ones of the position if the player can't move.
+        let (best_move, win_rate) = match Self::most_visited_child(&tree, 0) {
+            Some(child) if tree[0].player == Some(me) => {
+                (tree[child].last_move, tree[child].win_rate())
+            }
+            _ => (None, 1.0 - tree[0].win_rate()),
+        };
+
+        SearchResult {
+            best_move,
+            score: (win_rate * 200.0 - 100.0).round() as i32,
+            exact: false,
+            from_book: false,
+            depth: principal_variation.len() as u8,
+            principal_variation,
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+        }
+    }
+
+    fn move_count(&self) -> u32 {
+        self.move_count.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::endgame::EndgameSolver;
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn mcts_find_a_move() {
+        let board = Board::new_start();
+        let mcts = Mcts::new(100);
+        let result = mcts.search(&board, Player::Black);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.best_move, Some(result.principal_variation[0]));
+        assert!((-100..=100).contains(&result.score));
+        assert!(result.nodes > 100);
+    }
+
+    #[test]
+    fn same_seed_gives_same_moves() {
+        let board = Board::new_start()
+            .play(Player::Black, 5, 4)
+            .unwrap()
+            .unwrap();
+        let first = Mcts::new(200).with_seed(42).search(&board, Player::White);
+        let second = Mcts::new(200).with_seed(42).search(&board, Player::White);
+        assert_eq!(first.principal_variation, second.principal_variation);
+        assert_eq!(first.score, second.score);
+        assert_eq!(first.nodes, second.nodes);
+    }
+
+    #[test]
+    fn mcts_finds_the_winning_move_of_an_endgame() {
+        let board = Board::from_position(
+            "OOOOXXXO X-OXOXXX X-XOXOXX XXOXOOXX XXXXXOXO \
+             OOOOOOO- ---XOOOO ---X-OOO",
+        )
+        .unwrap();
+        let solution = EndgameSolver::new().solve(&board, Player::Black);
+        let (x, y) = Mcts::new(2000).compute_move(&board, Player::Black).unwrap();
+        let board_after_move = board.play(Player::Black, x, y).unwrap().unwrap();
+        let reply = EndgameSolver::new().solve(&board_after_move, Player::White);
+        assert_eq!(solution.score.signum(), -reply.score.signum());
+    }
+
+    #[test]
+    fn mcts_knows_the_game_is_over() {
+        let board = Board::from_position(&"X".repeat(64)).unwrap();
+        let result = Mcts::new(10).search(&board, Player::White);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, -100);
+    }
+
+    fn frozen_clock() -> Duration {
+        Duration::from_secs(0)
+    }
+
+    #[test]
+    fn mcts_with_time_limit_does_at_least_one_playout() {
+        let board = Board::new_start();
+        let time_limit = TimeLimit::with_clock(Duration::from_secs(0), frozen_clock);
+        let mcts = Mcts::new(1000).with_time_limit(time_limit);
+        let result = mcts.search(&board, Player::Black);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.principal_variation.len(), 1);
+    }
+}
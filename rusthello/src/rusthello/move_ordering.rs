@@ -0,0 +1,102 @@
+use super::board::*;
+
+/// Priority of the preferred move (ie the best move of a previous search).
+const PREFERRED_MOVE: i32 = i32::MAX;
+/// Corners can't be captured, they're the best moves most of the time.
+const CORNER: i32 = 100;
+/// Cells diagonally adjacent to the corners (X-squares) often give corners
+/// to the opponent, they're the worst moves most of the time.
+const X_SQUARE: i32 = -100;
+
+/// Returns the legal moves of the position, the most promising first, to
+/// help alpha-beta searches to prune the game tree.
+/// The preferred move comes first, then corners, then other moves according
+/// to the mobility they leave to the opponent (the lower the better), and
+/// X-squares come last. Moves of same priority are kept in grid order.
+pub fn order_moves(board: &Board, player: Player, preferred: Option<(u8, u8)>) -> Vec<(u8, u8)> {
+    let mut moves: Vec<(i32, (u8, u8))> = board
+        .legal_moves(player)
+        .map(|(x, y)| (priority(board, player, x, y, preferred), (x, y)))
+        .collect();
+    moves.sort_by_key(|(priority, _)| -(*priority as i64));
+
+    moves.into_iter().map(|(_, position)| position).collect()
+}
+
+/// Returns the priority of a legal move, the greater the better.
+fn priority(board: &Board, player: Player, x: u8, y: u8, preferred: Option<(u8, u8)>) -> i32 {
+    if preferred == Some((x, y)) {
+        return PREFERRED_MOVE;
+    }
+
+    let board_after_move = board
+        .play(player, x, y)
+        .expect("Unexpected error while ordering moves.")
+        .expect("Unexpected invalid move while ordering moves.");
+    let opponent_mobility = board_after_move.mobility(player.opponent()) as i32;
+
+    static_priority(x, y) - opponent_mobility
+}
+
+fn static_priority(x: u8, y: u8) -> i32 {
+    let on_border = |c: u8| c == 0 || c == 7;
+    let next_to_border = |c: u8| c == 1 || c == 6;
+    if on_border(x) && on_border(y) {
+        CORNER
+    } else if next_to_border(x) && next_to_border(y) {
+        X_SQUARE
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preferred_move_comes_first() {
+        let board = Board::new_start();
+        let moves = order_moves(&board, Player::Black, Some((4, 5)));
+        assert_eq!(moves[0], (4, 5));
+        assert_eq!(moves.len(), 4);
+    }
+
+    #[test]
+    fn corners_come_first_and_x_squares_last() {
+        let board = Board::from_position(
+            "--------\
+             -O------\
+             --X-----\
+             ---OX---\
+             ---XX---\
+             -----O--\
+             --------\
+             --------",
+        )
+        .unwrap();
+        let moves = order_moves(&board, Player::Black, None);
+        assert!(moves.len() > 2);
+        assert_eq!(moves.first(), Some(&(0, 0)));
+        assert_eq!(moves.last(), Some(&(6, 6)));
+    }
+
+    #[test]
+    fn moves_leaving_less_mobility_to_the_opponent_come_first() {
+        let board = Board::new_start()
+            .play(Player::Black, 5, 4)
+            .unwrap()
+            .unwrap();
+        let moves = order_moves(&board, Player::White, None);
+        let mobilities: Vec<u8> = moves
+            .iter()
+            .map(|&(x, y)| {
+                let board = board.play(Player::White, x, y).unwrap().unwrap();
+                board.mobility(Player::Black)
+            })
+            .collect();
+        let mut sorted_mobilities = mobilities.clone();
+        sorted_mobilities.sort_unstable();
+        assert_eq!(mobilities, sorted_mobilities);
+    }
+}
@@ -0,0 +1,348 @@
+use std::io::{self, BufRead, Write};
+
+use super::game::*;
+use super::notation::*;
+use super::virtual_player::{SearchResult, VirtualPlayer};
+
+/// The name of the engine, as reported to NBoard.
+const ENGINE_NAME: &str = "Rusthello";
+
+/// An engine speaking the NBoard protocol, to be driven by Othello GUIs : it
+/// reads commands (ie `set game`, `move`, `go`, `hint`) and answers them.
+/// Unknown commands are ignored, as the protocol requires.
+pub struct NBoardEngine {
+    game: Game,
+    new_player: Box<dyn Fn(u8) -> Box<dyn VirtualPlayer>>,
+    player: Box<dyn VirtualPlayer>,
+    // the player analyzing moves for hints, one ply shallower as each move
+    // is played before being searched.
+    analyst: Box<dyn VirtualPlayer>,
+}
+
+impl NBoardEngine {
+    /// Creates an engine searching at the given depth, with the virtual
+    /// players built by `new_player` from a depth. The depth changes with
+    /// the `set depth` command.
+    pub fn new(depth: u8, new_player: impl Fn(u8) -> Box<dyn VirtualPlayer> + 'static) -> Self {
+        Self {
+            game: Game::new(),
+            player: new_player(depth),
+            analyst: new_player(Self::analyst_depth(depth)),
+            new_player: Box::new(new_player),
+        }
+    }
+
+    /// Sets the depth of the searches, hints included.
+    fn set_depth(&mut self, depth: u8) {
+        self.player = (self.new_player)(depth);
+        self.analyst = (self.new_player)(Self::analyst_depth(depth));
+    }
+
+    /// Returns the depth of the analyst for searches of the given depth.
+    fn analyst_depth(depth: u8) -> u8 {
+        depth.saturating_sub(1).max(1)
+    }
+
+    /// Returns the game known by the engine.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Reads commands until `quit` or the end of the input, writing the
+    /// answers as soon as they're known. Invalid commands are reported by a
+    /// `status` line.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim() == "quit" {
+                break;
+            }
+            let answers = self
+                .execute(&line)
+                .unwrap_or_else(|message| vec![format!("status {}", message)]);
+            for answer in answers {
+                writeln!(output, "{}", answer)?;
+            }
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes a command, returning the lines to answer.
+    pub fn execute(&mut self, command: &str) -> Result<Vec<String>, String> {
+        let command = command.trim();
+        let (name, arguments) = match command.find(' ') {
+            Some(index) => (&command[..index], command[index + 1..].trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            "nboard" => Ok(vec![format!("set myname {}", ENGINE_NAME)]),
+            "set" => self.set(arguments),
+            "move" => {
+                let player = self
+                    .game
+                    .player()
+                    .ok_or_else(|| "The game is over.".to_string())?;
+                if parse_ggf_move(arguments)?.is_some() {
+                    self.game.play_ggf_move(player, arguments)?;
+                } else {
+                    // The pass was already played by the game, when the
+                    // opponent was blocked.
+                    self.game.check_pass(player.opponent())?;
+                }
+                Ok(Vec::new())
+            }
+            "go" => Ok(vec![
+                "status Thinking".to_string(),
+                self.best_move(),
+                "status".to_string(),
+            ]),
+            "hint" => {
+                let count = arguments
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid count of hints '{}'.", arguments))?;
+                let mut answers = vec!["status Analyzing".to_string()];
+                answers.extend(self.hints(count));
+                answers.push("status".to_string());
+                Ok(answers)
+            }
+            "ping" => Ok(vec![format!("pong {}", arguments)]),
+            // There is nothing to learn without a book to update.
+            "learn" => Ok(vec!["learned".to_string()]),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Executes a `set` command : `set game`, `set depth` or `set contempt`.
+    fn set(&mut self, arguments: &str) -> Result<Vec<String>, String> {
+        let (name, value) = match arguments.find(' ') {
+            Some(index) => (&arguments[..index], arguments[index + 1..].trim()),
+            None => (arguments, ""),
+        };
+
+        match name {
+            "game" => self.game = Game::from_ggf(value)?,
+            "depth" => match value.parse::<u8>() {
+                Ok(depth) if (1..=60).contains(&depth) => self.set_depth(depth),
+                _ => return Err(format!("Invalid depth '{}'.", value)),
+            },
+            _ => (),
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Searches the move of the current player, as a `===` answer, with its
+    /// evaluation when it's known in discs.
+    fn best_move(&self) -> String {
+        let player = match self.game.player() {
+            Some(player) => player,
+            None => return "=== PA".to_string(),
+        };
+        let result = self.player.search(self.game.board(), player);
+        let (x, y) = match result.best_move {
+            Some(best_move) => best_move,
+            None => return "=== PA".to_string(),
+        };
+        let square = square_to_string(x, y).to_uppercase();
+        // The protocol has no way to give the time without the evaluation.
+        match evaluation(&result) {
+            Some(evaluation) => format!(
+                "=== {}/{}/{:.2}",
+                square,
+                evaluation,
+                result.elapsed.as_secs_f64()
+            ),
+            None => format!("=== {}", square),
+        }
+    }
+
+    /// Analyzes the moves of the current player, as `search` answers, the
+    /// best moves first. NBoard requires evaluations in discs : the moves
+    /// only evaluated by a heuristic aren't given.
+    fn hints(&self, count: usize) -> Vec<String> {
+        let player = match self.game.player() {
+            Some(player) => player,
+            None => return Vec::new(),
+        };
+        self.analyst
+            .analyze(self.game.board(), player)
+            .iter()
+            .take(count)
+            .filter_map(|result| {
+                let (x, y) = result.best_move?;
+                Some(format!(
+                    "search {} {} 0 {}",
+                    square_to_string(x, y).to_uppercase(),
+                    evaluation(result)?,
+                    result.depth
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Returns the evaluation of a search for NBoard, in discs : the final disc
+/// differential of solved positions, or the largest one for won and lost
+/// games. Heuristic scores have no known scale in discs (the evaluators
+/// differ, and MCTS estimates chances to win), so they give None.
+fn evaluation(result: &SearchResult) -> Option<i32> {
+    match result.score {
+        score if result.exact => Some(score),
+        i32::MAX => Some(64),
+        score if score == -i32::MAX => Some(-64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::board::*;
+    use super::super::test_support::{run, GGF_START};
+    use super::super::virtual_player::AlphaBeta;
+    use super::*;
+    use std::time::Duration;
+
+    fn new_engine() -> NBoardEngine {
+        NBoardEngine::new(2, |depth| {
+            Box::new(AlphaBeta::new(depth).with_endgame_solver(10))
+        })
+    }
+
+    fn answers(engine: &mut NBoardEngine, input: &str) -> Vec<String> {
+        run(engine, input).lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn engine_introduces_itself_and_answers_pings() {
+        let mut engine = new_engine();
+        let output = answers(
+            &mut engine,
+            "nboard 2\nset depth 4\nset contempt 0\nping 1\n",
+        );
+        assert_eq!(output, vec!["set myname Rusthello", "pong 1"]);
+    }
+
+    #[test]
+    fn engine_plays_from_the_given_game() {
+        let mut engine = new_engine();
+        let input = format!(
+            "set game {}B[F5];)\nmove D6\ngo\nping 2\nquit\nping 3\n",
+            GGF_START
+        );
+        let output = answers(&mut engine, &input);
+        assert_eq!(output.len(), 4);
+        assert_eq!(output[0], "status Thinking");
+        assert!(output[1].starts_with("=== "));
+        assert_eq!(output[2], "status");
+        assert_eq!(output[3], "pong 2");
+
+        // The move is answered but not played : NBoard sends it back.
+        let (x, y) = parse_ggf_move(&output[1][4..]).unwrap().unwrap();
+        assert_eq!(engine.game().to_transcript(), Ok("f5d6".to_string()));
+        assert!(engine
+            .game()
+            .board()
+            .is_move_valid(Player::Black, x, y)
+            .unwrap());
+    }
+
+    #[test]
+    fn engine_gives_hints_the_best_first() {
+        let mut engine = new_engine();
+        let position = "OOOOOO-O*O****O-**OOOOOO*OOOOO--*-OOOOO**OOO-O-**-****O******--O";
+        let input = format!("set game (;GM[Othello]BO[8 {} *];)\nhint 3\n", position);
+        let output = answers(&mut engine, &input);
+        assert_eq!(
+            output,
+            vec![
+                "status Analyzing",
+                "search F8 20 0 10",
+                "search G6 14 0 10",
+                "search H4 12 0 10",
+                "status",
+            ]
+        );
+    }
+
+    #[test]
+    fn heuristic_evaluations_arent_given() {
+        let mut engine = new_engine();
+        let input = format!("set game {};)\nhint 3\n", GGF_START);
+        let output = answers(&mut engine, &input);
+        assert_eq!(output, vec!["status Analyzing", "status"]);
+        let answer = &engine.execute("go").unwrap()[1];
+        assert_eq!(answer.len(), "=== F5".len());
+    }
+
+    #[test]
+    fn evaluations_are_given_in_discs() {
+        let result = |score, exact| SearchResult {
+            best_move: None,
+            score,
+            exact,
+            from_book: false,
+            principal_variation: Vec::new(),
+            depth: 1,
+            nodes: 0,
+            elapsed: Duration::from_secs(0),
+        };
+        assert_eq!(evaluation(&result(-6, true)), Some(-6));
+        assert_eq!(evaluation(&result(23, false)), None);
+        assert_eq!(evaluation(&result(i32::MAX, false)), Some(64));
+        assert_eq!(evaluation(&result(-i32::MAX, false)), Some(-64));
+    }
+
+    #[test]
+    fn engine_reports_invalid_commands() {
+        let mut engine = new_engine();
+        let output = answers(
+            &mut engine,
+            "move A1\nset depth 0\nhint x\nlearn\nunknown\n",
+        );
+        assert_eq!(output.len(), 4);
+        assert!(output[..3]
+            .iter()
+            .all(|answer| answer.starts_with("status ")));
+        assert_eq!(output[3], "learned");
+        assert_eq!(engine.game().to_transcript(), Ok("".to_string()));
+    }
+
+    #[test]
+    fn engine_accepts_the_passes_sent_by_the_gui() {
+        let mut engine = new_engine();
+        let position = format!("*O-O{} *", "-".repeat(60));
+        let input = format!(
+            "set game (;GM[Othello]BO[8 {}];)\nmove PA\nmove C1\nmove PA\nping 1\n",
+            position
+        );
+        let output = answers(&mut engine, &input);
+        // No one had to pass at first, but White can't move after C1.
+        assert_eq!(output.len(), 2);
+        assert_eq!(output[0], "status White didn't have to pass.");
+        assert_eq!(output[1], "pong 1");
+        assert_eq!(engine.game().player(), Some(Player::Black));
+        assert_eq!(
+            engine.game().to_position(),
+            format!("XXXO{} X", "-".repeat(60))
+        );
+    }
+
+    #[test]
+    fn engine_passes_when_it_cant_move() {
+        let mut engine = new_engine();
+        let position = format!("*O{} O", "-".repeat(62));
+        let output = answers(
+            &mut engine,
+            &format!("set game (;GM[Othello]BO[8 {}];)\n", position),
+        );
+        assert!(output.is_empty());
+        // White passed, it's the turn of Black.
+        assert!(engine.execute("go").unwrap()[1].starts_with("=== C1/"));
+        engine.execute("move C1").unwrap();
+        assert!(engine.game().game_over());
+        assert_eq!(engine.execute("go").unwrap()[1], "=== PA");
+    }
+}
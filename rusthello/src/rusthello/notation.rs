@@ -0,0 +1,519 @@
+use super::board::*;
+use super::game::*;
+
+/// Returns the usual name of a square, ie `a1` for `(0, 0)` and `h8` for
+/// `(7, 7)`.
+pub fn square_to_string(x: u8, y: u8) -> String {
+    format!("{}{}", (b'a' + x) as char, y + 1)
+}
+
+/// Parses the name of a square (ie `f5` or `F5`) and returns its coordinates.
+pub fn parse_square(square: &str) -> Result<(u8, u8), String> {
+    let bytes = square.as_bytes();
+    if bytes.len() != 2 {
+        return Err(format!("'{}' is not a square", square));
+    }
+
+    let column = bytes[0].to_ascii_lowercase();
+    let row = bytes[1];
+    if !(b'a'..=b'h').contains(&column) || !(b'1'..=b'8').contains(&row) {
+        return Err(format!("'{}' is not a square", square));
+    }
+
+    Ok((column - b'a', row - b'1'))
+}
+
+/// Parses a move of the GGF format, used by the NBoard protocol : a square
+/// (ie `F5`) or `PA` for a pass, optionally followed by its evaluation and
+/// the time spent (ie `F5/1.50/2.1`). Returns None for a pass.
+pub fn parse_ggf_move(text: &str) -> Result<Option<(u8, u8)>, String> {
+    let square = text.split('/').next().unwrap().trim();
+    if square.eq_ignore_ascii_case("pa") {
+        return Ok(None);
+    }
+    parse_square(square).map(Some)
+}
+
+impl Board {
+    /// Exports the board as a one-line position, made of 64 cells from `a1`
+    /// to `h8`, row by row : `X` for black, `O` for white and `-` for empty.
+    pub fn to_position(&self) -> String {
+        self.iter()
+            .map(|(_, _, piece)| match piece {
+                Some(Player::Black) => 'X',
+                Some(Player::White) => 'O',
+                None => '-',
+            })
+            .collect()
+    }
+
+    /// Creates a board from a position, as exported by `to_position`.
+    /// Whitespaces are ignored, `x` or `*` are accepted for black, `o` for
+    /// white, and `.` or `_` for empty cells.
+    pub fn from_position(position: &str) -> Result<Board, String> {
+        let cells: Vec<char> = position.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != 64 {
+            return Err(format!(
+                "A position is made of 64 cells, {} found.",
+                cells.len()
+            ));
+        }
+
+        let mut board = Board::new();
+        for ((x, y), cell) in GridIterator::new().zip(cells) {
+            board.set_piece(x, y, parse_cell(cell, x, y)?)?;
+        }
+
+        Ok(board)
+    }
+}
+
+/// Parses a position cell.
+fn parse_cell(cell: char, x: u8, y: u8) -> Result<Option<Player>, String> {
+    match cell {
+        'X' | 'x' | '*' => Ok(Some(Player::Black)),
+        'O' | 'o' => Ok(Some(Player::White)),
+        '-' | '.' | '_' => Ok(None),
+        _ => Err(format!(
+            "Invalid cell '{}' at {} in the position.",
+            cell,
+            square_to_string(x, y)
+        )),
+    }
+}
+
+/// Returns the GGF tag of the moves of a player.
+fn ggf_color(player: Player) -> &'static str {
+    match player {
+        Player::Black => "B",
+        Player::White => "W",
+    }
+}
+
+/// Returns the GGF symbol of a player in a position.
+fn ggf_player(player: Player) -> &'static str {
+    match player {
+        Player::Black => "*",
+        Player::White => "O",
+    }
+}
+
+impl Game {
+    /// Exports the current position of the game on one line : the 64 cells
+    /// (see `Board::to_position`), a space and the next player, `X` for
+    /// black or `O` for white. If the game is over the next player is `-`.
+    pub fn to_position(&self) -> String {
+        let player = match self.player() {
+            Some(Player::Black) => 'X',
+            Some(Player::White) => 'O',
+            None => '-',
+        };
+        format!("{} {}", self.board().to_position(), player)
+    }
+
+    /// Creates a game starting from a position, as exported by `to_position`.
+    /// Whitespaces are ignored.
+    pub fn from_position(position: &str) -> Result<Game, String> {
+        let chars: Vec<char> = position.chars().filter(|c| !c.is_whitespace()).collect();
+        if chars.len() != 65 {
+            return Err(format!(
+                "A position is made of 64 cells and the next player, {} characters found.",
+                chars.len()
+            ));
+        }
+
+        let cells: String = chars[..64].iter().collect();
+        let board = Board::from_position(&cells)?;
+        let player = match chars[64] {
+            'X' | 'x' | '*' => Player::Black,
+            'O' | 'o' => Player::White,
+            '-' => {
+                let game = Game::from_board(board, Player::Black);
+                return if game.game_over() {
+                    Ok(game)
+                } else {
+                    Err("The game isn't over, the next player is required.".to_string())
+                };
+            }
+            other => return Err(format!("Invalid next player '{}' in the position.", other)),
+        };
+
+        Ok(Game::from_board(board, player))
+    }
+
+    /// Exports the moves leading to the current position using the compact
+    /// transcript format, ie `f5d6c3d3c4`. Passes are implicit.
+    /// As a transcript is replayed from the start position, it fails for a
+    /// game started from another position, see `to_ggf`.
+    pub fn to_transcript(&self) -> Result<String, String> {
+        if self.start_position() != (&Board::new_start(), Player::Black) {
+            return Err(
+                "The game doesn't start from the start position, it has no transcript.".to_string(),
+            );
+        }
+
+        Ok(self
+            .history()
+            .iter()
+            .filter_map(|played_move| match *played_move {
+                Move::Play { x, y, .. } => Some(square_to_string(x, y)),
+                Move::Pass { .. } => None,
+            })
+            .collect())
+    }
+
+    /// Creates a game from a GGF record, as sent by the NBoard protocol, ie
+    /// `(;GM[Othello]PB[me]PW[you]TY[8]BO[8 -...-O*...- *]B[F5]W[F6//0.1];)`.
+    /// Only the starting position and the moves are read.
+    pub fn from_ggf(ggf: &str) -> Result<Game, String> {
+        let ggf = ggf.trim();
+        let content = ggf
+            .strip_prefix("(;")
+            .and_then(|ggf| ggf.strip_suffix(";)"))
+            .ok_or_else(|| "A GGF game starts with '(;' and ends with ';)'.".to_string())?;
+
+        let mut game = None;
+        let mut rest = content;
+        while let Some(open) = rest.find('[') {
+            let tag = rest[..open].trim();
+            let close = rest[open..]
+                .find(']')
+                .ok_or_else(|| format!("The GGF tag {} isn't closed.", tag))?
+                + open;
+            let value = &rest[open + 1..close];
+            rest = &rest[close + 1..];
+            match tag {
+                "TY" if value.trim() != "8" => {
+                    return Err(format!("Unsupported GGF game type '{}'.", value))
+                }
+                "BO" => {
+                    let value = value.trim();
+                    let position = value
+                        .strip_prefix('8')
+                        .ok_or_else(|| format!("Unsupported GGF board '{}'.", value))?;
+                    game = Some(Game::from_position(position)?);
+                }
+                "B" | "W" => {
+                    let game = game
+                        .as_mut()
+                        .ok_or_else(|| "The GGF moves precede the board.".to_string())?;
+                    let player = if tag == "B" {
+                        Player::Black
+                    } else {
+                        Player::White
+                    };
+                    game.play_ggf_move(player, value)?;
+                }
+                _ => (),
+            }
+        }
+
+        game.ok_or_else(|| "The GGF game has no board.".to_string())
+    }
+
+    /// Plays a move of the GGF format for the given player, see
+    /// `parse_ggf_move`. As passes are implicit in a game, a pass is only
+    /// checked, see `check_pass`.
+    pub fn play_ggf_move(&mut self, player: Player, text: &str) -> Result<(), String> {
+        match parse_ggf_move(text)? {
+            None => self.check_pass(player),
+            Some((x, y)) => self.play(player, x, y),
+        }
+    }
+
+    /// Exports the game as a GGF record, see `from_ggf` : the starting
+    /// position and the moves leading to the current position.
+    pub fn to_ggf(&self) -> String {
+        let (board, player) = self.start_position();
+        let mut ggf = format!(
+            "(;GM[Othello]PC[Rusthello]TY[8]BO[8 {} {}]",
+            board.to_position().replace('X', "*"),
+            ggf_player(player)
+        );
+        for played_move in self.history() {
+            let text = match *played_move {
+                Move::Play { x, y, .. } => square_to_string(x, y).to_uppercase(),
+                Move::Pass { .. } => "PA".to_string(),
+            };
+            ggf.push_str(&format!("{}[{}]", ggf_color(played_move.player()), text));
+        }
+        ggf.push_str(";)");
+
+        ggf
+    }
+
+    /// Creates a game from a transcript, ie `f5d6c3d3c4`. The transcript is
+    /// case insensitive, whitespaces are ignored, and passes are implicit.
+    /// Errors name the offending ply, the first move being the ply 1.
+    pub fn from_transcript(transcript: &str) -> Result<Game, String> {
+        let mut game = Game::new();
+        let chars: Vec<char> = transcript.chars().filter(|c| !c.is_whitespace()).collect();
+
+        for (index, square) in chars.chunks(2).enumerate() {
+            let ply = index + 1;
+            let square: String = square.iter().collect();
+            let (x, y) = parse_square(&square)
+                .map_err(|message| format!("Invalid transcript at ply {} : {}.", ply, message))?;
+            let player = game.player().ok_or_else(|| {
+                format!(
+                    "Invalid transcript at ply {} : the game is over, '{}' can't be played.",
+                    ply, square
+                )
+            })?;
+            game.play(player, x, y).map_err(|_| {
+                format!(
+                    "Invalid transcript at ply {} : {} can't play at '{}'.",
+                    ply, player, square
+                )
+            })?;
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::GGF_START;
+    use super::*;
+
+    #[test]
+    fn square_to_string_uses_lowercase_letter_and_digit() {
+        assert_eq!(square_to_string(0, 0), "a1");
+        assert_eq!(square_to_string(5, 4), "f5");
+        assert_eq!(square_to_string(7, 7), "h8");
+    }
+
+    #[test]
+    fn parse_square_accepts_both_cases() {
+        assert_eq!(parse_square("f5"), Ok((5, 4)));
+        assert_eq!(parse_square("F5"), Ok((5, 4)));
+        assert_eq!(parse_square("h8"), Ok((7, 7)));
+    }
+
+    #[test]
+    fn parse_square_rejects_invalid_squares() {
+        assert!(parse_square("i1").is_err());
+        assert!(parse_square("a9").is_err());
+        assert!(parse_square("a0").is_err());
+        assert!(parse_square("a").is_err());
+        assert!(parse_square("a10").is_err());
+    }
+
+    const START_POSITION: &str =
+        "---------------------------OX------XO--------------------------- X";
+
+    #[test]
+    fn board_to_position_lists_all_cells() {
+        let board = Board::new_start();
+        assert_eq!(board.to_position(), &START_POSITION[..64]);
+    }
+
+    #[test]
+    fn board_from_position_reads_all_cells() {
+        let board = Board::from_position(
+            "X-------\
+             --------\
+             --------\
+             ---OX---\
+             ---XO---\
+             --------\
+             --------\
+             -------O",
+        )
+        .unwrap();
+        assert_eq!(board.get_piece(0, 0).unwrap(), Some(Player::Black));
+        assert_eq!(board.get_piece(7, 7).unwrap(), Some(Player::White));
+        assert_eq!(board.get_piece(4, 3).unwrap(), Some(Player::Black));
+        assert_eq!(board.count_pieces(), (3, 3));
+    }
+
+    #[test]
+    fn board_from_position_accepts_alternative_cells() {
+        let board = Board::from_position(&"*o._".repeat(16)).unwrap();
+        assert_eq!(board.to_position(), "XO--".repeat(16));
+    }
+
+    #[test]
+    fn board_from_position_rejects_invalid_positions() {
+        let error = Board::from_position("X-O").err().unwrap();
+        assert_eq!(error, "A position is made of 64 cells, 3 found.");
+        let error = Board::from_position(&format!("{}?", "-".repeat(63)))
+            .err()
+            .unwrap();
+        assert_eq!(error, "Invalid cell '?' at h8 in the position.");
+    }
+
+    #[test]
+    fn game_to_position_includes_the_next_player() {
+        let game = Game::new();
+        assert_eq!(game.to_position(), START_POSITION);
+        let game = Game::from_transcript("f5").unwrap();
+        assert!(game.to_position().ends_with(" O"));
+    }
+
+    #[test]
+    fn game_from_position_starts_with_the_given_player() {
+        let game = Game::from_position(START_POSITION).unwrap();
+        assert_eq!(game.player(), Some(Player::Black));
+        assert_eq!(game.count_pieces(), (2, 2));
+        let game = Game::from_position(&START_POSITION.replace(" X", " O")).unwrap();
+        assert_eq!(game.player(), Some(Player::White));
+    }
+
+    #[test]
+    fn game_from_position_manages_game_over() {
+        let position = format!("{} -", "X".repeat(64));
+        let game = Game::from_position(&position).unwrap();
+        assert!(game.game_over());
+        assert_eq!(game.to_position(), position);
+        let error = Game::from_position(&START_POSITION.replace(" X", " -"))
+            .err()
+            .unwrap();
+        assert_eq!(error, "The game isn't over, the next player is required.");
+    }
+
+    #[test]
+    fn game_from_position_rejects_invalid_next_player() {
+        let error = Game::from_position(&START_POSITION.replace(" X", " Y"))
+            .err()
+            .unwrap();
+        assert_eq!(error, "Invalid next player 'Y' in the position.");
+        assert!(Game::from_position(&START_POSITION[..64]).is_err());
+    }
+
+    #[test]
+    fn from_transcript_plays_all_moves() {
+        let game = Game::from_transcript("f5d6c3d3c4").unwrap();
+        assert_eq!(game.ply(), 5);
+        assert_eq!(game.player(), Some(Player::White));
+        assert_eq!(game.board().get_piece(2, 3).unwrap(), Some(Player::Black));
+    }
+
+    #[test]
+    fn from_transcript_ignores_case_and_whitespaces() {
+        let game = Game::from_transcript(" F5 d6\nC3 ").unwrap();
+        assert_eq!(game.to_transcript(), Ok("f5d6c3".to_string()));
+    }
+
+    #[test]
+    fn from_transcript_manages_implicit_passes() {
+        // White is blocked after a8, then black plays twice.
+        let transcript = "d3c5b6f3f5c6c7c2b1b7a8g2";
+        let game = Game::from_transcript(transcript).unwrap();
+        assert!(game.game_over());
+        assert_eq!(game.winner(), Some(Player::Black));
+        assert_eq!(
+            game.history()[11],
+            Move::Pass {
+                player: Player::White
+            }
+        );
+        assert_eq!(game.to_transcript(), Ok(transcript.to_string()));
+    }
+
+    #[test]
+    fn from_transcript_names_the_offending_ply() {
+        let error = Game::from_transcript("f5d6a1").err().unwrap();
+        assert_eq!(
+            error,
+            "Invalid transcript at ply 3 : Black can't play at 'a1'."
+        );
+        let error = Game::from_transcript("f5z6").err().unwrap();
+        assert_eq!(error, "Invalid transcript at ply 2 : 'z6' is not a square.");
+        let error = Game::from_transcript("f5d").err().unwrap();
+        assert_eq!(error, "Invalid transcript at ply 2 : 'd' is not a square.");
+    }
+
+    #[test]
+    fn from_transcript_rejects_moves_after_the_game_is_over() {
+        let error = Game::from_transcript("d3c5b6f3f5c6c7c2b1b7a8g2h1")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            "Invalid transcript at ply 13 : the game is over, 'h1' can't be played."
+        );
+    }
+
+    #[test]
+    fn to_transcript_stops_at_the_current_position() {
+        let mut game = Game::from_transcript("f5d6c3").unwrap();
+        game.undo().unwrap();
+        assert_eq!(game.to_transcript(), Ok("f5d6".to_string()));
+    }
+
+    #[test]
+    fn to_transcript_is_read_back_by_from_transcript() {
+        let transcript = Game::from_transcript("f5d6c3d3c4")
+            .unwrap()
+            .to_transcript()
+            .unwrap();
+        assert_eq!(
+            Game::from_transcript(&transcript).unwrap().to_transcript(),
+            Ok("f5d6c3d3c4".to_string())
+        );
+
+        // The moves of a game started elsewhere can't be replayed.
+        let mut game = Game::from_position(&Game::new().to_position()).unwrap();
+        game.play(Player::Black, 5, 4).unwrap();
+        assert_eq!(game.to_transcript(), Ok("f5".to_string()));
+        let position = format!("XO-O{} X", "-".repeat(60));
+        let mut game = Game::from_position(&position).unwrap();
+        game.play(Player::Black, 2, 0).unwrap();
+        assert!(game.to_transcript().is_err());
+    }
+
+    #[test]
+    fn parse_ggf_move_reads_squares_and_passes() {
+        assert_eq!(parse_ggf_move("F5"), Ok(Some((5, 4))));
+        assert_eq!(parse_ggf_move("d6/1.50/2.1"), Ok(Some((3, 5))));
+        assert_eq!(parse_ggf_move("PA"), Ok(None));
+        assert_eq!(parse_ggf_move("pa//0.1"), Ok(None));
+        assert!(parse_ggf_move("Z9").is_err());
+    }
+
+    #[test]
+    fn from_ggf_reads_the_board_and_the_moves() {
+        let game = Game::from_ggf(&format!("{}B[F5]W[D6//0.1]B[C3/1.2/3];)", GGF_START)).unwrap();
+        assert_eq!(game.to_transcript(), Ok("f5d6c3".to_string()));
+        assert_eq!(game.player(), Some(Player::White));
+
+        let position = format!("XO{} O", "-".repeat(62)).replace('X', "*");
+        let game = Game::from_ggf(&format!("(;GM[Othello]BO[8 {}]W[PA]B[C1];)", position)).unwrap();
+        assert!(game.game_over());
+    }
+
+    #[test]
+    fn from_ggf_rejects_invalid_games() {
+        assert!(Game::from_ggf("GM[Othello]").is_err());
+        assert!(Game::from_ggf(&format!("{}B[F5]W[F5];)", GGF_START)).is_err());
+        assert!(Game::from_ggf(&format!("{}B[PA];)", GGF_START)).is_err());
+        assert!(Game::from_ggf("(;GM[Othello]B[F5];)").is_err());
+        assert!(Game::from_ggf("(;GM[Othello]TY[10]BO[10 ...];)").is_err());
+        assert!(Game::from_ggf("(;GM[Othello];)").is_err());
+    }
+
+    #[test]
+    fn to_ggf_is_read_back_by_from_ggf() {
+        let mut game = Game::from_transcript("f5d6c3d3c4").unwrap();
+        game.undo().unwrap();
+        let ggf = game.to_ggf();
+        assert!(ggf.starts_with("(;GM[Othello]PC[Rusthello]TY[8]BO[8 ---"));
+        assert!(ggf.ends_with(" *]B[F5]W[D6]B[C3]W[D3];)"));
+        assert_eq!(
+            Game::from_ggf(&ggf).unwrap().to_transcript(),
+            Ok("f5d6c3d3".to_string())
+        );
+
+        let position = format!("XO-O{} O", "-".repeat(60));
+        let mut game = Game::from_position(&position).unwrap();
+        game.play(Player::Black, 2, 0).unwrap();
+        let ggf = game.to_ggf();
+        assert!(ggf.ends_with(" O]W[PA]B[C1]W[PA];)"));
+        let game = Game::from_ggf(&ggf).unwrap();
+        assert_eq!(game.start_position().1, Player::White);
+        assert_eq!(game.history().len(), 3);
+        assert_eq!(game.to_position(), format!("XXXO{} X", "-".repeat(60)));
+    }
+}
@@ -0,0 +1,431 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use super::board::*;
+use super::game::*;
+use super::random::Random;
+use super::virtual_player::{SearchResult, VirtualPlayer};
+
+/// How a move is chosen among the moves of the book.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BookChoice {
+    /// The move of greatest weight, the first one in grid order among moves
+    /// of equal weight.
+    Best,
+    /// A random move, the greater its weight the more likely.
+    WeightedRandom,
+}
+
+/// A move of the opening book, and its weight : the sum of the weights of
+/// the openings playing it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BookMove {
+    pub x: u8,
+    pub y: u8,
+    pub weight: u32,
+}
+
+/// A collection of openings, each one being a sequence of moves from the
+/// start position. Positions are looked up whatever the moves leading to
+/// them, and symmetric positions share their moves : they're recorded for
+/// the canonical form of the positions.
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    positions: HashMap<(Board, Player), Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    /// Creates an empty book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a book from a text having one opening per line : a transcript
+    /// (ie `f5d6c3d3c4`) optionally followed by its weight (1 by default).
+    /// Empty lines and lines starting with `#` are ignored.
+    /// Errors name the offending line, the first line being the line 1.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut book = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let transcript = fields.next().unwrap();
+            let weight = match fields.next() {
+                None => 1,
+                Some(weight) => weight.parse::<u32>().map_err(|_| {
+                    format!(
+                        "Invalid opening book at line {} : '{}' is not a weight.",
+                        index + 1,
+                        weight
+                    )
+                })?,
+            };
+            if let Some(extra) = fields.next() {
+                return Err(format!(
+                    "Invalid opening book at line {} : unexpected '{}'.",
+                    index + 1,
+                    extra
+                ));
+            }
+
+            book.add_opening(transcript, weight).map_err(|message| {
+                format!("Invalid opening book at line {} : {}", index + 1, message)
+            })?;
+        }
+
+        Ok(book)
+    }
+
+    /// Loads a book from a file, see `from_text` for its format.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| {
+            format!(
+                "Can't read the opening book {} : {}.",
+                path.display(),
+                error
+            )
+        })?;
+        Self::from_text(&text)
+    }
+
+    /// Adds an opening given as a transcript (ie `f5d6c3d3c4`), with the given
+    /// weight.
+    pub fn add_opening(&mut self, transcript: &str, weight: u32) -> Result<(), String> {
+        let game = Game::from_transcript(transcript)?;
+        let mut board = Board::new_start();
+        for played_move in game.history() {
+            if let Move::Play { player, x, y } = *played_move {
+                self.add_move(&board, player, x, y, weight);
+                board = board
+                    .play(player, x, y)
+                    .expect("Unexpected error while adding an opening.")
+                    .expect("Unexpected invalid move while adding an opening.");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a move of the canonical form of the position. When the
+    /// canonical position is symmetric, the symmetric moves are recorded too
+    /// (ie `f5`, `e6`, `d3` and `c4` from the start position).
+    fn add_move(&mut self, board: &Board, player: Player, x: u8, y: u8, weight: u32) {
+        let (canonical, symmetry) = board.canonical();
+        let (x, y) = symmetry.apply(x, y);
+        let mut equivalent_moves: Vec<(u8, u8)> = Vec::new();
+        for position_symmetry in canonical.symmetries() {
+            let equivalent_move = position_symmetry.apply(x, y);
+            if !equivalent_moves.contains(&equivalent_move) {
+                equivalent_moves.push(equivalent_move);
+            }
+        }
+
+        let moves = self.positions.entry((canonical, player)).or_default();
+        for (x, y) in equivalent_moves {
+            match moves
+                .iter_mut()
+                .find(|book_move| (book_move.x, book_move.y) == (x, y))
+            {
+                Some(book_move) => book_move.weight += weight,
+                None => moves.push(BookMove { x, y, weight }),
+            }
+        }
+    }
+
+    /// Returns the count of positions of the book.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Tells if the book has no position.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the moves of the book for the given position, in grid order.
+    pub fn moves(&self, board: &Board, player: Player) -> Vec<BookMove> {
+        let (canonical, symmetry) = board.canonical();
+        let book_moves = match self.positions.get(&(canonical, player)) {
+            Some(book_moves) => book_moves,
+            None => return Vec::new(),
+        };
+        let mut moves: Vec<BookMove> = book_moves
+            .iter()
+            .map(|book_move| {
+                let (x, y) = symmetry.inverse().apply(book_move.x, book_move.y);
+                BookMove { x, y, ..*book_move }
+            })
+            .collect();
+        moves.sort_by_key(|book_move| (book_move.y, book_move.x));
+
+        moves
+    }
+
+    /// Returns the move of greatest weight for the given position, if it's in
+    /// the book.
+    pub fn best_move(&self, board: &Board, player: Player) -> Option<(u8, u8)> {
+        self.moves(board, player)
+            .iter()
+            .fold(None, |best: Option<&BookMove>, book_move| match best {
+                Some(best) if best.weight >= book_move.weight => Some(best),
+                _ => Some(book_move),
+            })
+            .map(|book_move| (book_move.x, book_move.y))
+    }
+
+    /// Returns a random move for the given position, if it's in the book. The
+    /// greater the weight of a move, the more likely it is.
+    fn random_move(&self, board: &Board, player: Player, random: &mut Random) -> Option<(u8, u8)> {
+        let moves = self.moves(board, player);
+        let total_weight: u64 = moves.iter().map(|book_move| book_move.weight as u64).sum();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut remaining = random.next_u64() % total_weight;
+        for book_move in moves {
+            if remaining < book_move.weight as u64 {
+                return Some((book_move.x, book_move.y));
+            }
+            remaining -= book_move.weight as u64;
+        }
+
+        None
+    }
+}
+
+/// A virtual player playing the moves of an opening book, and the moves found
+/// by another virtual player once out of the book.
+///
+/// Book moves aren't searched : they're reported `from_book`, with a depth of
+/// 0 and a neutral score.
+pub struct BookPlayer<P: VirtualPlayer> {
+    book: OpeningBook,
+    player: P,
+    choice: BookChoice,
+    random: RefCell<Random>,
+}
+
+impl<P: VirtualPlayer> BookPlayer<P> {
+    /// The default seed of the random generator.
+    const DEFAULT_SEED: u64 = 0x0123_4567_89ab_cdef;
+
+    /// Creates a player using the given book, then the given player. Book
+    /// moves are chosen randomly, according to their weights.
+    pub fn new(book: OpeningBook, player: P) -> Self {
+        Self {
+            book,
+            player,
+            choice: BookChoice::WeightedRandom,
+            random: RefCell::new(Random::new(Self::DEFAULT_SEED)),
+        }
+    }
+
+    /// Sets how book moves are chosen.
+    pub fn with_choice(mut self, choice: BookChoice) -> Self {
+        self.choice = choice;
+        self
+    }
+
+    /// Sets the seed of the random generator, the same seed giving the same
+    /// moves.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = RefCell::new(Random::new(seed));
+        self
+    }
+
+    /// Returns the book, ie to replace it.
+    pub fn book_mut(&mut self) -> &mut OpeningBook {
+        &mut self.book
+    }
+
+    /// Returns the book move to play, if the position is in the book.
+    fn book_move(&self, board: &Board, me: Player) -> Option<(u8, u8)> {
+        match self.choice {
+            BookChoice::Best => self.book.best_move(board, me),
+            BookChoice::WeightedRandom => {
+                self.book
+                    .random_move(board, me, &mut self.random.borrow_mut())
+            }
+        }
+    }
+}
+
+impl<P: VirtualPlayer> VirtualPlayer for BookPlayer<P> {
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        match self.book_move(board, me) {
+            Some(book_move) => SearchResult {
+                best_move: Some(book_move),
+                score: 0,
+                exact: false,
+                from_book: true,
+                principal_variation: vec![book_move],
+                depth: 0,
+                nodes: 0,
+                elapsed: Duration::from_secs(0),
+            },
+            None => self.player.search(board, me),
+        }
+    }
+
+    fn move_count(&self) -> u32 {
+        self.player.move_count()
+    }
+
+    /// The book has no evaluation of its moves, they're all analyzed by the
+    /// virtual player.
+    fn analyze(&self, board: &Board, me: Player) -> Vec<SearchResult> {
+        self.player.analyze(board, me)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::notation::square_to_string;
+    use super::super::symmetry::Symmetry;
+    use super::super::virtual_player::AlphaBeta;
+    use super::*;
+
+    #[test]
+    fn book_records_the_symmetric_openings() {
+        let book = OpeningBook::from_text("f5d6").unwrap();
+        let board = Board::new_start();
+        let first_moves: Vec<(u8, u8)> = book
+            .moves(&board, Player::Black)
+            .iter()
+            .map(|book_move| (book_move.x, book_move.y))
+            .collect();
+        // d3, c4, f5 and e6.
+        assert_eq!(first_moves, vec![(3, 2), (2, 3), (5, 4), (4, 5)]);
+        let game = Game::from_transcript("c4").unwrap();
+        let reply = book.best_move(game.board(), Player::White).unwrap();
+        assert_eq!(square_to_string(reply.0, reply.1), "e3");
+        assert_eq!(book.len(), 2);
+    }
+
+    #[test]
+    fn book_finds_transpositions() {
+        let book = OpeningBook::from_text("f5d6c3d3c4f4").unwrap();
+        // c3 and c4 are played in the other order.
+        let game = Game::from_transcript("f5d6c4d3c3").unwrap();
+        assert_eq!(book.best_move(game.board(), Player::White), Some((5, 3)));
+    }
+
+    #[test]
+    fn book_finds_symmetric_positions() {
+        let book = OpeningBook::from_text("f5d6c3").unwrap();
+        let game = Game::from_transcript("f5d6").unwrap();
+        let rotated = game.board().transform(Symmetry::Rotate90);
+        assert_eq!(
+            book.best_move(&rotated, Player::Black),
+            Some(Symmetry::Rotate90.apply(2, 2))
+        );
+    }
+
+    #[test]
+    fn book_sums_the_weights_of_openings() {
+        let book = OpeningBook::from_text(
+            "# Two openings sharing their first moves.\n\
+             f5d6c3 3\n\
+             \n\
+             f5f6 2\n\
+             f5d6c5",
+        )
+        .unwrap();
+        let game = Game::from_transcript("f5").unwrap();
+        let moves = book.moves(game.board(), Player::White);
+        assert_eq!(
+            moves,
+            &[
+                BookMove {
+                    x: 3,
+                    y: 5,
+                    weight: 4
+                },
+                BookMove {
+                    x: 5,
+                    y: 5,
+                    weight: 2
+                },
+            ]
+        );
+        assert_eq!(book.best_move(game.board(), Player::White), Some((3, 5)));
+    }
+
+    #[test]
+    fn invalid_books_are_rejected() {
+        let error = OpeningBook::from_text("f5d6\nf5f5").unwrap_err();
+        assert!(error.starts_with("Invalid opening book at line 2 :"));
+        assert!(OpeningBook::from_text("f5d6 many").is_err());
+        assert!(OpeningBook::from_text("f5d6 1 2").is_err());
+        assert!(OpeningBook::from_file("no/such/book.txt").is_err());
+    }
+
+    #[test]
+    fn the_provided_book_is_valid() {
+        let book = OpeningBook::from_text(include_str!("../../books/openings.txt")).unwrap();
+        assert!(!book.moves(&Board::new_start(), Player::Black).is_empty());
+    }
+
+    #[test]
+    fn book_player_plays_book_moves_then_searches() {
+        let book = OpeningBook::from_text("f5d6c3").unwrap();
+        let player = BookPlayer::new(book, AlphaBeta::new(2)).with_choice(BookChoice::Best);
+        let game = Game::from_transcript("f5d6").unwrap();
+        let result = player.search(game.board(), Player::Black);
+        assert_eq!(result.best_move, Some((2, 2)));
+        assert!(result.from_book);
+        assert_eq!(result.depth, 0);
+        assert_eq!(player.move_count(), 0);
+        let game = Game::from_transcript("f5d6c3").unwrap();
+        let result = player.search(game.board(), Player::White);
+        assert!(!result.from_book);
+        assert_eq!(result.depth, 2);
+        assert!(player.move_count() > 0);
+    }
+
+    #[test]
+    fn book_player_analyzes_moves_with_the_virtual_player() {
+        let book = OpeningBook::from_text(include_str!("../../books/openings.txt")).unwrap();
+        let player = BookPlayer::new(book, AlphaBeta::new(3));
+        let game = Game::from_transcript("f5").unwrap();
+        // The scores and variations are the ones of the virtual player alone.
+        let lines = |results: Vec<SearchResult>| -> Vec<(i32, Vec<(u8, u8)>)> {
+            results
+                .into_iter()
+                .map(|result| (result.score, result.principal_variation))
+                .collect()
+        };
+        let results = player.analyze(game.board(), Player::White);
+        assert!(results.iter().all(|result| result.depth == 4));
+        assert_eq!(
+            lines(results),
+            lines(AlphaBeta::new(3).analyze(game.board(), Player::White))
+        );
+    }
+
+    #[test]
+    fn book_player_chooses_randomly_according_to_weights() {
+        let book = OpeningBook::from_text("f5d6 1\nf5f6 1\nf5f4 0").unwrap();
+        let game = Game::from_transcript("f5").unwrap();
+        let player = BookPlayer::new(book, AlphaBeta::new(2)).with_seed(7);
+        let mut seen = Vec::new();
+        for _ in 0..50 {
+            let book_move = player.compute_move(game.board(), Player::White).unwrap();
+            if !seen.contains(&book_move) {
+                seen.push(book_move);
+            }
+        }
+        seen.sort_unstable();
+        // f4 is never played, as its weight is 0.
+        assert_eq!(seen, vec![(3, 5), (5, 5)]);
+    }
+}
@@ -0,0 +1,474 @@
+use std::cell::{Cell, RefCell};
+
+use super::board::*;
+use super::evaluation::*;
+use super::move_ordering;
+use super::search::{next_player, normalize};
+use super::time_limit::*;
+use super::transposition_table::*;
+use super::virtual_player::{SearchResult, VirtualPlayer};
+
+/// Implementation of the Principal Variation Search algorithm, a negamax
+/// variant of alpha-beta.
+/// The first move of each position is searched with the whole window, the
+/// other ones with a null window only proving they aren't better. They're
+/// searched again with the whole window when the proof fails, which is rare
+/// with a good move ordering, hence the moves are ordered using a shallower
+/// search first.
+///
+/// The move found is the same as the one of AlphaBeta for the same depth,
+/// exploring fewer positions.
+pub struct Pvs<E: Evaluator = WeightedEvaluator> {
+    // depth of the current iteration.
+    depth: Cell<u8>,
+    max_depth: u8,
+    move_count: Cell<u32>,
+    table: RefCell<TranspositionTable>,
+    evaluator: E,
+    clock: Clock,
+}
+
+impl Pvs {
+    /// Creates a new Pvs, fixing its exploration depth.
+    pub fn new(depth: u8) -> Self {
+        Self::with_evaluator(depth, WeightedEvaluator::default())
+    }
+}
+
+impl<E: Evaluator> Pvs<E> {
+    /// Creates a new Pvs evaluating positions with the given evaluator.
+    pub fn with_evaluator(depth: u8, evaluator: E) -> Self {
+        Self {
+            depth: Cell::new(depth),
+            max_depth: depth,
+            move_count: Cell::new(0),
+            table: RefCell::new(TranspositionTable::new(TranspositionTable::DEFAULT_SIZE)),
+            evaluator,
+            clock: system_clock,
+        }
+    }
+
+    /// Sets the count of entries of the transposition table (0 to disable it).
+    pub fn with_table_size(mut self, table_size: usize) -> Self {
+        self.table = RefCell::new(TranspositionTable::new(table_size));
+        self
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the count of positions found in the transposition table.
+    pub fn transposition_hits(&self) -> u32 {
+        self.table.borrow().hits()
+    }
+
+    /// Searches the root position. Every move able to be the best one gets an
+    /// exact score, so that among moves of equal score the first one in grid
+    /// order is chosen, as AlphaBeta does.
+    fn search_root(&self, board: &Board, me: Player) -> Option<Variation> {
+        let key = board.zobrist_hash(me);
+        let preferred_move = self
+            .table
+            .borrow_mut()
+            .probe(key)
+            .map(|entry| entry.best_move);
+
+        let mut best: Option<Variation> = None;
+        for (x, y) in self.moves_to_explore(board, me, 1, preferred_move) {
+            let board_after_move = self.play(board, me, x, y);
+            let candidate = match best.as_ref() {
+                None => self.explore(&board_after_move, me, 1, i32::MIN, i32::MAX),
+                Some(best) => {
+                    // A move preceding the best one in grid order replaces
+                    // it if it's as good : the null window is lowered by one.
+                    let alpha = if (y, x) < best.first_move() {
+                        best.score.saturating_sub(1)
+                    } else {
+                        best.score
+                    };
+                    let candidate =
+                        self.explore(&board_after_move, me, 1, alpha, alpha.saturating_add(1));
+                    if candidate.score > alpha {
+                        self.explore(&board_after_move, me, 1, candidate.score - 1, i32::MAX)
+                    } else {
+                        candidate
+                    }
+                }
+            };
+
+            let candidate = candidate.after(x, y);
+            let better = match best.as_ref() {
+                None => true,
+                Some(best) => {
+                    candidate.score > best.score
+                        || (candidate.score == best.score
+                            && candidate.first_move() < best.first_move())
+                }
+            };
+            if better {
+                best = Some(candidate);
+            }
+        }
+
+        if let Some(best) = best.as_ref() {
+            let (y, x) = best.first_move();
+            self.table.borrow_mut().store(TranspositionEntry {
+                key,
+                depth: self.depth.get(),
+                bound: Bound::Exact,
+                evaluation: best.score,
+                best_move: (x, y),
+            });
+        }
+
+        best
+    }
+
+    /// Negamax implementation of the principal variation search, scores
+    /// being relative to the player to move. The score is exact inside the
+    /// window, a bound otherwise (fail-soft).
+    fn negamax(
+        &self,
+        board: &Board,
+        player: Player,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Variation {
+        let key = board.zobrist_hash(player);
+        let entry = self.table.borrow_mut().probe(key);
+        if let Some(known) = entry
+            .as_ref()
+            .and_then(|entry| self.known_variation(entry, depth, alpha, beta))
+        {
+            return known;
+        }
+
+        let preferred_move = entry.map(|entry| entry.best_move);
+        let mut best: Option<Variation> = None;
+        let mut current_alpha = alpha;
+        for (x, y) in self.moves_to_explore(board, player, depth, preferred_move) {
+            let board_after_move = self.play(board, player, x, y);
+            let candidate = if best.is_none() {
+                self.explore(&board_after_move, player, depth, current_alpha, beta)
+            } else {
+                // The move is expected to be worse than the best one.
+                let candidate = self.explore(
+                    &board_after_move,
+                    player,
+                    depth,
+                    current_alpha,
+                    current_alpha.saturating_add(1),
+                );
+                if candidate.score > current_alpha && candidate.score < beta {
+                    self.explore(&board_after_move, player, depth, candidate.score - 1, beta)
+                } else {
+                    candidate
+                }
+            };
+
+            let candidate = candidate.after(x, y);
+            if best
+                .as_ref()
+                .is_none_or(|best| candidate.score > best.score)
+            {
+                best = Some(candidate);
+            }
+            let best_score = best.as_ref().unwrap().score;
+            if best_score >= beta {
+                break;
+            }
+            current_alpha = current_alpha.max(best_score);
+        }
+
+        // The caller ensures the player can move.
+        let best = best.expect("Unexpected position without move.");
+        let (y, x) = best.first_move();
+        self.table.borrow_mut().store(TranspositionEntry {
+            key,
+            depth: self.depth.get() - depth + 1,
+            bound: Bound::for_window(best.score, alpha, beta),
+            evaluation: best.score,
+            best_move: (x, y),
+        });
+
+        best
+    }
+
+    /// Evaluates the position after a move of the given player, searching
+    /// deeper if the maximum depth isn't reached. The score is relative to
+    /// the given player, as the window.
+    fn explore(
+        &self,
+        board_after_move: &Board,
+        player: Player,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Variation {
+        let next_player = if depth < self.depth.get() {
+            next_player(board_after_move, player)
+        } else {
+            None
+        };
+
+        match next_player {
+            // max depth or blocked game, just evaluate.
+            None => {
+                let evaluation = self.evaluator.evaluate(board_after_move, player);
+                Variation::new(normalize(evaluation, player))
+            }
+            // the opponent can't move, the player plays again.
+            Some(next_player) if next_player == player => {
+                self.negamax(board_after_move, player, depth + 1, alpha, beta)
+            }
+            Some(next_player) => self
+                .negamax(
+                    board_after_move,
+                    next_player,
+                    depth + 1,
+                    beta.saturating_neg(),
+                    alpha.saturating_neg(),
+                )
+                .negated(),
+        }
+    }
+
+    /// Returns the variation stored in a transposition table entry, if it
+    /// allows to skip the search.
+    /// As for AlphaBeta only results of searches of the same depth are used.
+    fn known_variation(
+        &self,
+        entry: &TranspositionEntry,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<Variation> {
+        if entry.depth != self.depth.get() - depth + 1 {
+            return None;
+        }
+
+        let usable = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => entry.evaluation >= beta,
+            Bound::Upper => entry.evaluation <= alpha,
+        };
+        if usable {
+            let (x, y) = entry.best_move;
+            Some(Variation::new(entry.evaluation).after(x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the moves to explore, ordered except at the maximum depth
+    /// where all moves are evaluated.
+    fn moves_to_explore(
+        &self,
+        board: &Board,
+        player: Player,
+        depth: u8,
+        preferred_move: Option<(u8, u8)>,
+    ) -> Vec<(u8, u8)> {
+        if depth < self.depth.get() {
+            move_ordering::order_moves(board, player, preferred_move)
+        } else {
+            board.legal_moves(player).collect()
+        }
+    }
+
+    fn play(&self, board: &Board, player: Player, x: u8, y: u8) -> Board {
+        self.move_count.set(self.move_count() + 1);
+        board
+            .play(player, x, y)
+            .expect("Unexpected error while computing move.")
+            .expect("Unexpected invalid move while computing move.")
+    }
+}
+
+impl<E: Evaluator> VirtualPlayer for Pvs<E> {
+    fn move_count(&self) -> u32 {
+        self.move_count.get()
+    }
+
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let start = (self.clock)();
+        let move_count = self.move_count();
+        self.table.borrow_mut().new_search();
+        // Without knowledge of the position from a previous search, a
+        // shallower search (of same parity) fills the transposition table
+        // with the best moves, explored first by the real search.
+        let known = self
+            .table
+            .borrow_mut()
+            .probe(board.zobrist_hash(me))
+            .is_some();
+        if !known && self.max_depth > 2 {
+            self.depth.set(self.max_depth - 2);
+            self.search_root(board, me);
+        }
+        self.depth.set(self.max_depth);
+        let best = self.search_root(board, me);
+        let (best_move, score, principal_variation) = match best {
+            Some(best) => {
+                let (y, x) = best.first_move();
+                (Some((x, y)), best.score, best.moves)
+            }
+            None => {
+                let evaluation = self.evaluator.evaluate(board, me.opponent());
+                (None, normalize(evaluation, me), Vec::new())
+            }
+        };
+
+        SearchResult {
+            best_move,
+            score,
+            exact: false,
+            from_book: false,
+            principal_variation,
+            depth: self.max_depth,
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+        }
+    }
+}
+
+/// A score, relative to a player, and the moves leading to it.
+struct Variation {
+    score: i32,
+    moves: Vec<(u8, u8)>,
+}
+
+impl Variation {
+    fn new(score: i32) -> Self {
+        Self {
+            score,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Returns the variation preceded by the given move.
+    fn after(mut self, x: u8, y: u8) -> Self {
+        self.moves.insert(0, (x, y));
+        self
+    }
+
+    /// Returns the variation from the point of view of the opponent.
+    fn negated(mut self) -> Self {
+        self.score = -self.score;
+        self
+    }
+
+    /// Returns the first move, in grid order terms (y, x).
+    fn first_move(&self) -> (u8, u8) {
+        let (x, y) = self.moves[0];
+        (y, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::Game;
+    use super::super::virtual_player::{AlphaBeta, Minimax};
+    use super::*;
+
+    const POSITIONS: [&str; 3] = [
+        "f5d6c3d3c4f4f6f3e6e7",
+        "f5f6e6f4e3c5c4e7",
+        "c4e3f6e6f5c5f4g6f7",
+    ];
+
+    #[test]
+    fn pvs_find_a_move() {
+        let board = Board::new_start();
+        let pvs = Pvs::new(3);
+        let result = pvs.search(&board, Player::Black);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.best_move, Some(result.principal_variation[0]));
+        assert_eq!(result.nodes, pvs.move_count());
+    }
+
+    #[test]
+    fn pvs_reports_the_same_score_as_minimax() {
+        for transcript in POSITIONS.iter() {
+            let game = Game::from_transcript(transcript).unwrap();
+            let player = game.player().unwrap();
+            let minimax = Minimax::new(4).search(game.board(), player);
+            let pvs = Pvs::new(4).search(game.board(), player);
+            assert_eq!(pvs.best_move, minimax.best_move);
+            assert_eq!(pvs.score, minimax.score);
+        }
+    }
+
+    #[test]
+    fn pvs_finds_the_same_moves_as_alphabeta_exploring_fewer_positions() {
+        let mut pvs_moves = 0;
+        let mut alphabeta_moves = 0;
+        for transcript in POSITIONS.iter() {
+            let game = Game::from_transcript(transcript).unwrap();
+            let player = game.player().unwrap();
+            let pvs = Pvs::new(6);
+            let alphabeta = AlphaBeta::new(6);
+            assert_eq!(
+                pvs.compute_move(game.board(), player),
+                alphabeta.compute_move(game.board(), player)
+            );
+            pvs_moves += pvs.move_count();
+            alphabeta_moves += alphabeta.move_count();
+        }
+        assert!(pvs_moves < alphabeta_moves);
+    }
+
+    #[test]
+    fn pvs_uses_the_given_evaluator() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let pvs = Pvs::with_evaluator(4, WeightedEvaluator::classic());
+        let alphabeta = AlphaBeta::with_evaluator(4, WeightedEvaluator::classic());
+        assert_eq!(
+            pvs.compute_move(game.board(), player),
+            alphabeta.compute_move(game.board(), player)
+        );
+    }
+
+    #[test]
+    fn pvs_knows_the_game_is_over() {
+        let board = Board::from_position(&"X".repeat(64)).unwrap();
+        let result = Pvs::new(3).search(&board, Player::White);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, -i32::MAX);
+    }
+
+    /// This test take more time and is only done when the feature flag is activated.
+    /// Disabling capture show each 'best' move found, and the move counts per
+    /// algorithms.
+    #[cfg(feature = "pvsvsalphabeta")]
+    #[test]
+    fn pvs_behave_the_same_as_alpha_beta() {
+        let mut game = Game::new();
+        let alpha_beta = AlphaBeta::new(6);
+        let pvs = Pvs::new(6);
+        while !game.game_over() {
+            // compare computed moves for this turn.
+            let alphabeta_result = alpha_beta.compute_move(game.board(), game.player().unwrap());
+            let pvs_result = pvs.compute_move(game.board(), game.player().unwrap());
+            assert_eq!(alphabeta_result, pvs_result);
+            // play the move... and continue the game
+            println!(
+                "Move : {:?} / move counts : alphabeta {} - {} pvs",
+                pvs_result,
+                alpha_beta.move_count(),
+                pvs.move_count()
+            );
+            match pvs_result {
+                Some((x, y)) => game.play(game.player().unwrap(), x, y),
+                None => panic!("Unexpected empty move."),
+            }
+            .unwrap();
+        }
+        assert!(pvs.move_count() < alpha_beta.move_count());
+    }
+}
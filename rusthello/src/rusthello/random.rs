@@ -0,0 +1,54 @@
+/// A small pseudo-random generator (splitmix64), good enough for games and
+/// reproducible as it's seeded.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Creates a generator, the same seed giving the same sequence.
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next random number.
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number lower than the given bound (not null).
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        let mut c = Random::new(43);
+        let sequence_a: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let sequence_b: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let sequence_c: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_eq!(sequence_a, sequence_b);
+        assert_ne!(sequence_a, sequence_c);
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut random = Random::new(7);
+        let mut seen = [false; 5];
+        for _ in 0..100 {
+            seen[random.below(5)] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+}
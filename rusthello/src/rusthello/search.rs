@@ -0,0 +1,44 @@
+use super::board::*;
+
+/// Determines the next player after a move of `last_player`, or None if the
+/// game is over : the opponent, unless he can't move and passes.
+pub fn next_player(board: &Board, last_player: Player) -> Option<Player> {
+    if board.can_player_move(last_player.opponent()) {
+        Some(last_player.opponent())
+    } else if board.can_player_move(last_player) {
+        Some(last_player)
+    } else {
+        None
+    }
+}
+
+/// Returns an evaluation (greater is better for Black), normalized to be
+/// 'greater is better' for the player.
+pub fn normalize(evaluation: i32, player: Player) -> i32 {
+    match player {
+        Player::Black => evaluation,
+        Player::White => -evaluation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_player_passes_when_the_opponent_cant_move() {
+        let board = Board::new_start();
+        assert_eq!(next_player(&board, Player::Black), Some(Player::White));
+
+        let mut board = Board::new();
+        board.set_piece(0, 0, Some(Player::Black)).unwrap();
+        board.set_piece(1, 0, Some(Player::White)).unwrap();
+        board.set_piece(3, 0, Some(Player::White)).unwrap();
+        assert_eq!(next_player(&board, Player::Black), Some(Player::Black));
+        board.set_piece(2, 0, Some(Player::Black)).unwrap();
+        board.set_piece(1, 0, Some(Player::Black)).unwrap();
+        board.set_piece(4, 0, Some(Player::Black)).unwrap();
+        board.set_piece(3, 0, Some(Player::Black)).unwrap();
+        assert_eq!(next_player(&board, Player::White), None);
+    }
+}
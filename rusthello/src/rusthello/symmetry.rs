@@ -0,0 +1,217 @@
+use super::board::*;
+
+/// The 8 symmetries of the board, `a1` being the top left corner and `h8` the
+/// bottom right one. Rotations are clockwise.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirror across the vertical axis : `a1` becomes `h1`.
+    MirrorHorizontal,
+    /// Mirror across the horizontal axis : `a1` becomes `a8`.
+    MirrorVertical,
+    /// Mirror across the `a1`-`h8` diagonal : `b1` becomes `a2`.
+    Transpose,
+    /// Mirror across the `h1`-`a8` diagonal : `a1` becomes `h8`.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    /// All the symmetries, the identity first.
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::MirrorHorizontal,
+        Symmetry::MirrorVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Returns the coordinates of a square once transformed.
+    pub fn apply(self, x: u8, y: u8) -> (u8, u8) {
+        match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (7 - y, x),
+            Symmetry::Rotate180 => (7 - x, 7 - y),
+            Symmetry::Rotate270 => (y, 7 - x),
+            Symmetry::MirrorHorizontal => (7 - x, y),
+            Symmetry::MirrorVertical => (x, 7 - y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (7 - y, 7 - x),
+        }
+    }
+
+    /// Returns the symmetry cancelling this one.
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+
+    /// Transforms a bitboard, the bit `x + 8 * y` being moved as the square
+    /// `(x, y)`.
+    pub fn apply_bitboard(self, bitboard: u64) -> u64 {
+        match self {
+            Symmetry::Identity => bitboard,
+            Symmetry::Rotate90 => mirror_horizontal(transpose(bitboard)),
+            Symmetry::Rotate180 => bitboard.reverse_bits(),
+            Symmetry::Rotate270 => mirror_vertical(transpose(bitboard)),
+            Symmetry::MirrorHorizontal => mirror_horizontal(bitboard),
+            Symmetry::MirrorVertical => mirror_vertical(bitboard),
+            Symmetry::Transpose => transpose(bitboard),
+            Symmetry::AntiTranspose => anti_transpose(bitboard),
+        }
+    }
+}
+
+/// Reverses the bits of each row.
+fn mirror_horizontal(bitboard: u64) -> u64 {
+    const K1: u64 = 0x5555_5555_5555_5555;
+    const K2: u64 = 0x3333_3333_3333_3333;
+    const K4: u64 = 0x0f0f_0f0f_0f0f_0f0f;
+    let bitboard = ((bitboard >> 1) & K1) | ((bitboard & K1) << 1);
+    let bitboard = ((bitboard >> 2) & K2) | ((bitboard & K2) << 2);
+    ((bitboard >> 4) & K4) | ((bitboard & K4) << 4)
+}
+
+/// Reverses the order of the rows.
+fn mirror_vertical(bitboard: u64) -> u64 {
+    bitboard.swap_bytes()
+}
+
+/// Swaps rows and columns, by swapping bits across the diagonal by blocks.
+fn transpose(bitboard: u64) -> u64 {
+    const K1: u64 = 0x5500_5500_5500_5500;
+    const K2: u64 = 0x3333_0000_3333_0000;
+    const K4: u64 = 0x0f0f_0f0f_0000_0000;
+    let mut bitboard = bitboard;
+    let t = K4 & (bitboard ^ (bitboard << 28));
+    bitboard ^= t ^ (t >> 28);
+    let t = K2 & (bitboard ^ (bitboard << 14));
+    bitboard ^= t ^ (t >> 14);
+    let t = K1 & (bitboard ^ (bitboard << 7));
+    bitboard ^ t ^ (t >> 7)
+}
+
+/// Mirrors across the anti-diagonal : it's a transposition followed by a
+/// rotation of 180 degrees.
+fn anti_transpose(bitboard: u64) -> u64 {
+    transpose(bitboard).reverse_bits()
+}
+
+impl Board {
+    /// Returns the board transformed by the given symmetry.
+    pub fn transform(&self, symmetry: Symmetry) -> Board {
+        Board::from_bitboards(
+            symmetry.apply_bitboard(self.bitboard(Player::Black)),
+            symmetry.apply_bitboard(self.bitboard(Player::White)),
+        )
+        .expect("Unexpected overlapping pieces while transforming a board.")
+    }
+
+    /// Returns the canonical form of the board, the same for all symmetric
+    /// boards, and the symmetry transforming the board into it.
+    /// Moves are mapped to the canonical board with `symmetry.apply`, and
+    /// back with `symmetry.inverse().apply`.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .iter()
+            .map(|&symmetry| (self.transform(symmetry), symmetry))
+            .min_by_key(|(board, _)| (board.bitboard(Player::Black), board.bitboard(Player::White)))
+            .unwrap()
+    }
+
+    /// Returns the symmetries leaving the board unchanged, the identity
+    /// first.
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        Symmetry::ALL
+            .iter()
+            .copied()
+            .filter(|&symmetry| self.transform(symmetry) == *self)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::Game;
+    use super::*;
+
+    #[test]
+    fn bitboards_are_transformed_as_squares() {
+        for &symmetry in Symmetry::ALL.iter() {
+            for (x, y) in GridIterator::new() {
+                let (tx, ty) = symmetry.apply(x, y);
+                assert_eq!(
+                    symmetry.apply_bitboard(1 << (x + 8 * y)),
+                    1 << (tx + 8 * ty),
+                    "{:?} of ({}, {})",
+                    symmetry,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_cancels_the_symmetry() {
+        for &symmetry in Symmetry::ALL.iter() {
+            for (x, y) in GridIterator::new() {
+                let (tx, ty) = symmetry.apply(x, y);
+                assert_eq!(symmetry.inverse().apply(tx, ty), (x, y));
+            }
+        }
+        assert_eq!(Symmetry::Rotate90.apply(0, 0), (7, 0));
+    }
+
+    #[test]
+    fn symmetric_boards_have_the_same_canonical_form() {
+        let board = *Game::from_transcript("f5d6c3d3c4").unwrap().board();
+        let (canonical, _) = board.canonical();
+        for &symmetry in Symmetry::ALL.iter() {
+            let (other_canonical, other_symmetry) = board.transform(symmetry).canonical();
+            assert_eq!(other_canonical.to_position(), canonical.to_position());
+            assert_eq!(
+                board
+                    .transform(symmetry)
+                    .transform(other_symmetry)
+                    .to_position(),
+                canonical.to_position()
+            );
+        }
+    }
+
+    #[test]
+    fn moves_are_mapped_through_the_symmetry() {
+        let board = *Game::from_transcript("f5d6").unwrap().board();
+        let (canonical, symmetry) = board.canonical();
+        let (x, y) = symmetry.apply(2, 2);
+        let after_move = board.play(Player::Black, 2, 2).unwrap().unwrap();
+        let canonical_after_move = canonical.play(Player::Black, x, y).unwrap().unwrap();
+        assert_eq!(
+            after_move.transform(symmetry).to_position(),
+            canonical_after_move.to_position()
+        );
+        assert_eq!(symmetry.inverse().apply(x, y), (2, 2));
+    }
+
+    #[test]
+    fn start_position_has_four_symmetries() {
+        assert_eq!(
+            Board::new_start().symmetries(),
+            vec![
+                Symmetry::Identity,
+                Symmetry::Rotate180,
+                Symmetry::Transpose,
+                Symmetry::AntiTranspose
+            ]
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the tests of the engines speaking a protocol over
+//! their input and output.
+
+use std::io;
+
+use super::gtp::GtpEngine;
+use super::nboard::NBoardEngine;
+
+/// The header of a GGF game from the start position, as sent by NBoard.
+pub const GGF_START: &str = "(;GM[Othello]PC[NBoard]PB[me]PW[you]RE[?]TI[15:00]TY[8]\
+    BO[8 ---------------------------O*------*O--------------------------- *]";
+
+/// An engine reading commands from an input and answering them on an output.
+pub trait Engine {
+    fn run(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+}
+
+impl Engine for NBoardEngine {
+    fn run(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        NBoardEngine::run(self, input, output)
+    }
+}
+
+impl Engine for GtpEngine {
+    fn run(&mut self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        GtpEngine::run(self, input, output)
+    }
+}
+
+/// Runs an engine on the given input, returning its output.
+pub fn run(engine: &mut impl Engine, input: &str) -> String {
+    let mut output = Vec::new();
+    engine.run(input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// A clock returns the time elapsed since any fixed origin.
+/// It allows to measure time where `std::time` is not available (ie WASM in a
+/// browser).
+pub type Clock = fn() -> Duration;
+
+/// The default clock, based on `std::time::Instant`.
+pub fn system_clock() -> Duration {
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    ORIGIN.get_or_init(Instant::now).elapsed()
+}
+
+/// A time budget given to a search.
+#[derive(Debug, Copy, Clone)]
+pub struct TimeLimit {
+    budget: Duration,
+    clock: Clock,
+}
+
+impl TimeLimit {
+    /// Creates a time limit measured with the system clock.
+    pub fn new(budget: Duration) -> Self {
+        Self::with_clock(budget, system_clock)
+    }
+
+    /// Creates a time limit measured with the given clock.
+    pub fn with_clock(budget: Duration, clock: Clock) -> Self {
+        Self { budget, clock }
+    }
+
+    /// Returns the time budget.
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Returns the clock measuring the time.
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// Starts to spend the time budget, returns the deadline.
+    pub fn start(&self) -> Deadline {
+        let start = (self.clock)();
+        Deadline {
+            start,
+            end: start + self.budget,
+            clock: self.clock,
+        }
+    }
+}
+
+/// The moment when a time budget expires.
+#[derive(Debug, Copy, Clone)]
+pub struct Deadline {
+    start: Duration,
+    end: Duration,
+    clock: Clock,
+}
+
+impl Deadline {
+    /// Is the time budget spent ?
+    pub fn expired(&self) -> bool {
+        (self.clock)() >= self.end
+    }
+
+    /// Returns the time elapsed since the start.
+    pub fn elapsed(&self) -> Duration {
+        (self.clock)().saturating_sub(self.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frozen_clock() -> Duration {
+        Duration::from_secs(42)
+    }
+
+    #[test]
+    fn deadline_expires_after_the_budget() {
+        let deadline = TimeLimit::new(Duration::from_millis(10)).start();
+        assert!(!deadline.expired());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(deadline.expired());
+        assert!(deadline.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn deadline_uses_the_given_clock() {
+        let deadline = TimeLimit::with_clock(Duration::from_millis(10), frozen_clock).start();
+        assert!(!deadline.expired());
+        assert_eq!(deadline.elapsed(), Duration::from_secs(0));
+        let deadline = TimeLimit::with_clock(Duration::from_millis(0), frozen_clock).start();
+        assert!(deadline.expired());
+    }
+}
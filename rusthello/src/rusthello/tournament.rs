@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use super::board::*;
+use super::game::*;
+use super::virtual_player::VirtualPlayer;
+
+/// A round robin tournament between virtual players : each player meets each
+/// other one from every opening, once with each color.
+pub struct Tournament {
+    players: Vec<(String, Box<dyn VirtualPlayer>)>,
+    openings: Vec<(Board, Player)>,
+}
+
+/// The result of a game of a tournament. Players are given by their index,
+/// in the order they were added.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GameRecord {
+    pub black: usize,
+    pub white: usize,
+    /// The index of the opening the game started from.
+    pub opening: usize,
+    pub black_discs: u8,
+    pub white_discs: u8,
+}
+
+impl GameRecord {
+    /// Returns the disc differential from the point of view of Black.
+    pub fn differential(&self) -> i32 {
+        self.black_discs as i32 - self.white_discs as i32
+    }
+}
+
+/// The results of a player over a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The sum of the disc differentials of the games of the player.
+    pub discs: i32,
+}
+
+impl Standing {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            discs: 0,
+        }
+    }
+
+    /// Records a game, given its disc differential for the player.
+    fn record(&mut self, differential: i32) {
+        match differential {
+            d if d > 0 => self.wins += 1,
+            0 => self.draws += 1,
+            _ => self.losses += 1,
+        }
+        self.discs += differential;
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Returns the fraction of points scored, a draw being worth half a win.
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// Estimates the Elo difference between the player and its average
+    /// opponent from its score. It's None when the player won or lost all
+    /// its games, as the difference can't be estimated.
+    pub fn elo(&self) -> Option<f64> {
+        let score = self.score();
+        if score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+        Some(400.0 * (score / (1.0 - score)).log10())
+    }
+}
+
+impl Tournament {
+    /// Creates a tournament played from the given openings, as transcripts
+    /// (ie `f5d6`). An empty transcript is the start position.
+    pub fn new(openings: &[&str]) -> Result<Tournament, String> {
+        if openings.is_empty() {
+            return Err("A tournament needs at least one opening.".to_string());
+        }
+        let openings = openings
+            .iter()
+            .map(|transcript| {
+                let game = Game::from_transcript(transcript)?;
+                match game.player() {
+                    Some(player) => Ok((*game.board(), player)),
+                    None => Err(format!("The opening '{}' ends the game.", transcript)),
+                }
+            })
+            .collect::<Result<Vec<(Board, Player)>, String>>()?;
+
+        Ok(Tournament {
+            players: Vec::new(),
+            openings,
+        })
+    }
+
+    /// Adds a player to the tournament.
+    pub fn with_player(mut self, name: &str, player: Box<dyn VirtualPlayer>) -> Self {
+        self.players.push((name.to_string(), player));
+        self
+    }
+
+    /// Returns the count of games of the tournament.
+    pub fn game_count(&self) -> usize {
+        let players = self.players.len();
+        players * players.saturating_sub(1) * self.openings.len()
+    }
+
+    /// Plays all the games of the tournament, calling `on_game` after each
+    /// one, and returns the standings, the best players first.
+    pub fn run(&self, mut on_game: impl FnMut(&GameRecord)) -> Result<Vec<Standing>, String> {
+        let mut standings: Vec<Standing> = self
+            .players
+            .iter()
+            .map(|(name, _)| Standing::new(name))
+            .collect();
+
+        for first in 0..self.players.len() {
+            for second in first + 1..self.players.len() {
+                for (opening, &(board, player)) in self.openings.iter().enumerate() {
+                    for &(black, white) in [(first, second), (second, first)].iter() {
+                        let game = play_game(
+                            board,
+                            player,
+                            self.players[black].1.as_ref(),
+                            self.players[white].1.as_ref(),
+                        )?;
+                        let (black_discs, white_discs) = game.count_pieces();
+                        let record = GameRecord {
+                            black,
+                            white,
+                            opening,
+                            black_discs,
+                            white_discs,
+                        };
+                        standings[black].record(record.differential());
+                        standings[white].record(-record.differential());
+                        on_game(&record);
+                    }
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap()
+                .then(b.discs.cmp(&a.discs))
+        });
+        Ok(standings)
+    }
+}
+
+/// Plays a game between two virtual players from the given position, and
+/// returns the finished game.
+pub fn play_game(
+    board: Board,
+    player: Player,
+    black: &dyn VirtualPlayer,
+    white: &dyn VirtualPlayer,
+) -> Result<Game, String> {
+    let mut game = Game::from_board(board, player);
+    while let Some(player) = game.player() {
+        let virtual_player = match player {
+            Player::Black => black,
+            Player::White => white,
+        };
+        let (x, y) = virtual_player
+            .compute_move(game.board(), player)
+            .ok_or_else(|| format!("{} didn't find any move.", player))?;
+        game.play(player, x, y)?;
+    }
+
+    Ok(game)
+}
+
+/// Formats standings as a table, one player per line.
+pub fn standings_table(standings: &[Standing]) -> String {
+    let width = standings
+        .iter()
+        .map(|standing| standing.name.len())
+        .chain(Some(6))
+        .max()
+        .unwrap();
+    let mut table = format!(
+        "{:<width$}  games  wins  draws  losses  score  discs    elo\n",
+        "player",
+        width = width
+    );
+    for standing in standings {
+        let elo = match standing.elo() {
+            Some(elo) => format!("{:+.0}", elo),
+            None => "-".to_string(),
+        };
+        writeln!(
+            table,
+            "{:<width$}  {:>5}  {:>4}  {:>5}  {:>6}  {:>4.0}%  {:>+5}  {:>5}",
+            standing.name,
+            standing.games(),
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.score() * 100.0,
+            standing.discs,
+            elo,
+            width = width
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::virtual_player::AlphaBeta;
+    use super::*;
+
+    #[test]
+    fn play_game_plays_until_the_end() {
+        let player = AlphaBeta::new(1);
+        let game = play_game(Board::new_start(), Player::Black, &player, &player).unwrap();
+        assert!(game.game_over());
+        let (black, white) = game.count_pieces();
+        assert!(black + white > 4);
+    }
+
+    #[test]
+    fn tournament_plays_each_pair_with_both_colors() {
+        let tournament = Tournament::new(&["", "f5d6", "f5f6"])
+            .unwrap()
+            .with_player("depth 1", Box::new(AlphaBeta::new(1)))
+            .with_player("depth 2", Box::new(AlphaBeta::new(2)))
+            .with_player("depth 3", Box::new(AlphaBeta::new(3)));
+        assert_eq!(tournament.game_count(), 18);
+        let mut records = Vec::new();
+        let standings = tournament.run(|record| records.push(*record)).unwrap();
+
+        assert_eq!(records.len(), 18);
+        assert!(records.iter().all(|record| record.black != record.white));
+        assert_eq!(
+            records
+                .iter()
+                .filter(|record| record.black == 0 && record.white == 1)
+                .count(),
+            3
+        );
+        assert_eq!(standings.len(), 3);
+        for standing in standings.iter() {
+            assert_eq!(standing.games(), 12);
+        }
+        let wins: u32 = standings.iter().map(|standing| standing.wins).sum();
+        let losses: u32 = standings.iter().map(|standing| standing.losses).sum();
+        assert_eq!(wins, losses);
+        assert_eq!(
+            standings.iter().map(|standing| standing.discs).sum::<i32>(),
+            0
+        );
+        assert!(standings[0].score() >= standings[2].score());
+    }
+
+    #[test]
+    fn tournament_rejects_invalid_openings() {
+        assert!(Tournament::new(&[]).is_err());
+        assert!(Tournament::new(&["f5f5"]).is_err());
+    }
+
+    #[test]
+    fn elo_is_estimated_from_the_score() {
+        let mut standing = Standing::new("player");
+        assert_eq!(standing.score(), 0.5);
+        standing.record(10);
+        assert_eq!(standing.elo(), None);
+        standing.record(0);
+        standing.record(-4);
+        standing.record(2);
+        assert_eq!(standing.score(), 0.625);
+        assert_eq!(standing.discs, 8);
+        assert!((standing.elo().unwrap() - 88.7).abs() < 0.1);
+    }
+
+    #[test]
+    fn standings_table_has_a_line_per_player() {
+        let mut standing = Standing::new("a long player name");
+        standing.record(2);
+        let table = standings_table(&[standing, Standing::new("b")]);
+        assert_eq!(table.lines().count(), 3);
+        assert!(table
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("a long player name      1"));
+    }
+}
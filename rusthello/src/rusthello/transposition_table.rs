@@ -0,0 +1,213 @@
+/// Kind of evaluation stored in a transposition table entry, as searches
+/// with alpha-beta windows may only find bounds of the real evaluation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bound {
+    /// The evaluation is exact.
+    Exact,
+    /// The real evaluation is greater or equal.
+    Lower,
+    /// The real evaluation is lower or equal.
+    Upper,
+}
+
+impl Bound {
+    /// Returns the kind of the evaluation found by a search done with the
+    /// given alpha-beta window.
+    pub fn for_window(evaluation: i32, alpha: i32, beta: i32) -> Bound {
+        if evaluation <= alpha {
+            Bound::Upper
+        } else if evaluation >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        }
+    }
+}
+
+/// A position already searched.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TranspositionEntry {
+    /// The Zobrist hash of the position.
+    pub key: u64,
+    /// The depth of the search below the position.
+    pub depth: u8,
+    pub bound: Bound,
+    pub evaluation: i32,
+    pub best_move: (u8, u8),
+}
+
+/// A fixed size cache of searched positions, indexed by their Zobrist hash.
+///
+/// When two positions compete for the same slot, the new one replaces the
+/// old one if it was stored by a previous search, or if the new one was
+/// searched at least as deep.
+pub struct TranspositionTable {
+    entries: Vec<Option<(TranspositionEntry, u8)>>,
+    generation: u8,
+    probes: u32,
+    hits: u32,
+}
+
+impl TranspositionTable {
+    /// Default count of entries, using about 1.5 MB.
+    pub const DEFAULT_SIZE: usize = 1 << 16;
+
+    /// Creates a table. The size is rounded up to a power of two, a size of
+    /// zero creates a table that never stores anything.
+    pub fn new(size: usize) -> Self {
+        let size = if size == 0 {
+            0
+        } else {
+            size.next_power_of_two()
+        };
+        Self {
+            entries: vec![None; size],
+            generation: 0,
+            probes: 0,
+            hits: 0,
+        }
+    }
+
+    /// Returns the count of entries of the table.
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Marks the start of a new search, entries of previous searches will be
+    /// replaced first.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Forgets all entries and statistics.
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.probes = 0;
+        self.hits = 0;
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.entries.len() - 1)
+    }
+
+    /// Looks for a position in the table.
+    pub fn probe(&mut self, key: u64) -> Option<TranspositionEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        self.probes += 1;
+        match self.entries[self.index(key)] {
+            Some((entry, _)) if entry.key == key => {
+                self.hits += 1;
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+
+    /// Stores a position in the table, according to the replacement policy.
+    pub fn store(&mut self, entry: TranspositionEntry) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let index = self.index(entry.key);
+        let replace = match self.entries[index] {
+            None => true,
+            Some((old, generation)) => {
+                old.key == entry.key || generation != self.generation || entry.depth >= old.depth
+            }
+        };
+        if replace {
+            self.entries[index] = Some((entry, self.generation));
+        }
+    }
+
+    /// Returns the count of positions looked for.
+    pub fn probes(&self) -> u32 {
+        self.probes
+    }
+
+    /// Returns the count of positions found.
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: u64, depth: u8) -> TranspositionEntry {
+        TranspositionEntry {
+            key,
+            depth,
+            bound: Bound::Exact,
+            evaluation: 10,
+            best_move: (2, 3),
+        }
+    }
+
+    #[test]
+    fn size_is_rounded_to_a_power_of_two() {
+        assert_eq!(TranspositionTable::new(1000).size(), 1024);
+        assert_eq!(TranspositionTable::new(0).size(), 0);
+    }
+
+    #[test]
+    fn probe_returns_stored_entry() {
+        let mut table = TranspositionTable::new(16);
+        table.store(entry(42, 3));
+        assert_eq!(table.probe(42), Some(entry(42, 3)));
+        assert_eq!(table.probe(43), None);
+        assert_eq!(table.probes(), 2);
+        assert_eq!(table.hits(), 1);
+    }
+
+    #[test]
+    fn deeper_entries_are_kept_during_a_search() {
+        let mut table = TranspositionTable::new(16);
+        table.store(entry(1, 5));
+        table.store(entry(17, 3));
+        assert_eq!(table.probe(1), Some(entry(1, 5)));
+        table.store(entry(17, 5));
+        assert_eq!(table.probe(17), Some(entry(17, 5)));
+    }
+
+    #[test]
+    fn entries_of_previous_searches_are_replaced() {
+        let mut table = TranspositionTable::new(16);
+        table.store(entry(1, 5));
+        table.new_search();
+        table.store(entry(17, 3));
+        assert_eq!(table.probe(17), Some(entry(17, 3)));
+        assert_eq!(table.probe(1), None);
+    }
+
+    #[test]
+    fn empty_table_stores_nothing() {
+        let mut table = TranspositionTable::new(0);
+        table.store(entry(1, 5));
+        assert_eq!(table.probe(1), None);
+        assert_eq!(table.probes(), 0);
+    }
+
+    #[test]
+    fn clear_forgets_entries() {
+        let mut table = TranspositionTable::new(16);
+        table.store(entry(1, 5));
+        table.probe(1);
+        table.clear();
+        assert_eq!(table.hits(), 0);
+        assert_eq!(table.probe(1), None);
+    }
+
+    #[test]
+    fn bound_for_window() {
+        assert_eq!(Bound::for_window(-5, 0, 10), Bound::Upper);
+        assert_eq!(Bound::for_window(0, 0, 10), Bound::Upper);
+        assert_eq!(Bound::for_window(5, 0, 10), Bound::Exact);
+        assert_eq!(Bound::for_window(10, 0, 10), Bound::Lower);
+    }
+}
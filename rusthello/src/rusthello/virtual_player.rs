@@ -1,28 +1,199 @@
-use std::{cell::Cell, cmp};
+use std::{
+    cell::{Cell, RefCell},
+    cmp,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
 use super::board::*;
-use super::game_status::*;
+use super::endgame::*;
+use super::evaluation::*;
+use super::move_ordering;
+use super::search::{next_player, normalize};
+use super::time_limit::*;
+use super::transposition_table::*;
 
 /// The VirtualPlayer trait standardize the public interface of algorithms to
 /// find moves (virtual player, move suggestion, ...).
 pub trait VirtualPlayer {
+    /// Searches the 'best move' for the given board and player, reporting
+    /// its evaluation and the expected continuation.
+    fn search(&self, board: &Board, me: Player) -> SearchResult;
+
     /// Returns the 'best move' the given board and player.
-    fn compute_move(&self, board: &Board, me: Player) -> Option<(u8, u8)>;
+    fn compute_move(&self, board: &Board, me: Player) -> Option<(u8, u8)> {
+        self.search(board, me).best_move
+    }
 
     /// Returns the total count of move while exploring tree game.
     fn move_count(&self) -> u32;
+
+    /// Analyzes each move of the given player, returning its evaluation and
+    /// the expected continuation, the best moves first.
+    /// The position after each move is searched as a whole search (ie with
+    /// its own time budget), hence the depth is one more than the one of a
+    /// search.
+    fn analyze(&self, board: &Board, me: Player) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = board
+            .legal_moves(me)
+            .map(|(x, y)| {
+                let board_after_move = board
+                    .play(me, x, y)
+                    .expect("Unexpected error while analyzing moves.")
+                    .expect("Unexpected invalid move while analyzing moves.");
+                let (reply, score) = if !board_after_move.can_player_move(me.opponent())
+                    && board_after_move.can_player_move(me)
+                {
+                    // the opponent can't move, the player plays again.
+                    let reply = self.search(&board_after_move, me);
+                    let score = reply.score;
+                    (reply, score)
+                } else {
+                    let reply = self.search(&board_after_move, me.opponent());
+                    let score = -reply.score;
+                    (reply, score)
+                };
+
+                let mut principal_variation = vec![(x, y)];
+                principal_variation.extend(reply.principal_variation);
+                SearchResult {
+                    best_move: Some((x, y)),
+                    score,
+                    exact: reply.exact,
+                    from_book: false,
+                    principal_variation,
+                    depth: reply.depth + 1,
+                    nodes: reply.nodes + 1,
+                    elapsed: reply.elapsed,
+                }
+            })
+            .collect();
+        // The sort is stable, moves of equal evaluation stay in grid order.
+        results.sort_by_key(|result| cmp::Reverse(result.score));
+
+        results
+    }
+}
+
+/// A boxed virtual player is a virtual player, ie to wrap a virtual player
+/// chosen at runtime.
+impl<P: VirtualPlayer + ?Sized> VirtualPlayer for Box<P> {
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        (**self).search(board, me)
+    }
+
+    fn move_count(&self) -> u32 {
+        (**self).move_count()
+    }
+
+    fn analyze(&self, board: &Board, me: Player) -> Vec<SearchResult> {
+        (**self).analyze(board, me)
+    }
+}
+
+/// The result of a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// The best move, None if the player can't move.
+    pub best_move: Option<(u8, u8)>,
+    /// The evaluation of the best move from the point of view of the player
+    /// (greater is better). Unless `exact` is set, it's a heuristic
+    /// evaluation, won games being evaluated to `i32::MAX`.
+    pub score: i32,
+    /// Whether the position was solved, the score being then the final disc
+    /// differential with a perfect play.
+    pub exact: bool,
+    /// Whether the best move was played from an opening book, without any
+    /// search : the score, depth and statistics are then meaningless.
+    pub from_book: bool,
+    /// The expected continuation, starting with the best move. Passes are
+    /// implicit.
+    pub principal_variation: Vec<(u8, u8)>,
+    /// The depth of the search.
+    pub depth: u8,
+    /// The count of moves explored.
+    pub nodes: u32,
+    /// The time spent searching.
+    pub elapsed: Duration,
+}
+
+impl SearchResult {
+    /// Builds the result of a search from the best move found. If the player
+    /// can't move, the position itself is evaluated.
+    fn from_best_move<E: Evaluator>(
+        best_move: Option<BestMove>,
+        evaluator: &E,
+        board: &Board,
+        me: Player,
+        depth: u8,
+    ) -> Self {
+        match best_move {
+            None => {
+                let evaluation = evaluator.evaluate(board, me.opponent());
+                Self::without_move(depth, normalize(evaluation, me))
+            }
+            Some(best_move) => Self {
+                best_move: Some((best_move.x, best_move.y)),
+                score: best_move.normalized_evaluation(me),
+                exact: false,
+                from_book: false,
+                principal_variation: best_move.line(),
+                depth,
+                nodes: 0,
+                elapsed: Duration::from_secs(0),
+            },
+        }
+    }
+
+    /// Builds the result of a search when the player can't move.
+    fn without_move(depth: u8, score: i32) -> Self {
+        Self {
+            best_move: None,
+            score,
+            exact: false,
+            from_book: false,
+            principal_variation: Vec::new(),
+            depth,
+            nodes: 0,
+            elapsed: Duration::from_secs(0),
+        }
+    }
 }
 
 /// Implementation of the MiniMax algorithm.
-pub struct Minimax {
+pub struct Minimax<E: Evaluator = WeightedEvaluator> {
     depth: u8,
     move_count: Cell<u32>,
+    evaluator: E,
+    clock: Clock,
 }
 
 impl Minimax {
     /// Creates a new MiniMax with, fixing its exploration depth.
     pub fn new(depth: u8) -> Self {
-        Self { depth, move_count: Cell::new(0) }
+        Self::with_evaluator(depth, WeightedEvaluator::default())
+    }
+}
+
+impl<E: Evaluator> Minimax<E> {
+    /// Creates a new MiniMax evaluating positions with the given evaluator.
+    pub fn with_evaluator(depth: u8, evaluator: E) -> Self {
+        Self {
+            depth,
+            move_count: Cell::new(0),
+            evaluator,
+            clock: system_clock,
+        }
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
     }
 
     /// Minimax implementation.
@@ -32,21 +203,21 @@ impl Minimax {
         current_player: Player,
         depth: u8,
     ) -> Option<BestMove> {
-        GridIterator::new().fold(None, |best_move, (x, y)| {
-            let opt_board_after_move = board
-                .play(current_player, x, y)
-                .expect("Unexpected error while computing move.");
-
-            // is the move valid ?
-            if let Some(board_after_move) = opt_board_after_move {
+        board
+            .legal_moves(current_player)
+            .fold(None, |best_move, (x, y)| {
+                let board_after_move = board
+                    .play(current_player, x, y)
+                    .expect("Unexpected error while computing move.")
+                    .expect("Unexpected invalid move while computing move.");
                 self.move_count.set(self.move_count() + 1);
                 if depth == self.depth {
                     // max depth, just evaluate and returns
-                    let evaluation = Evaluator::evaluate(&board_after_move, current_player);
+                    let evaluation = self.evaluator.evaluate(&board_after_move, current_player);
                     return BestMove::best_move_for_player(
                         current_player,
                         best_move,
-                        Some(BestMove { x, y, evaluation }),
+                        Some(BestMove::new(x, y, evaluation)),
                     );
                 }
 
@@ -60,11 +231,11 @@ impl Minimax {
                         current_player
                     } else {
                         // the game is blocked.
-                        let evaluation = Evaluator::evaluate(&board_after_move, current_player);
+                        let evaluation = self.evaluator.evaluate(&board_after_move, current_player);
                         return BestMove::best_move_for_player(
                             current_player,
                             best_move,
-                            Some(BestMove { x, y, evaluation }),
+                            Some(BestMove::new(x, y, evaluation)),
                         );
                     }
                 };
@@ -72,223 +243,617 @@ impl Minimax {
                 let inner_best_move = self
                     .inner_compute_move(&board_after_move, next_player, depth + 1)
                     .unwrap();
-                let BestMove {
-                    x: _,
-                    y: _,
-                    evaluation,
-                } = inner_best_move;
-                return BestMove::best_move_for_player(
+                BestMove::best_move_for_player(
                     current_player,
                     best_move,
-                    Some(BestMove { x, y, evaluation }),
-                );
-            }
-
-            // it's not a valid move, just return the current best move.
-            best_move
-        })
+                    Some(BestMove::followed_by(x, y, inner_best_move)),
+                )
+            })
     }
 }
 
-impl VirtualPlayer for Minimax {
+impl<E: Evaluator> VirtualPlayer for Minimax<E> {
     fn move_count(&self) -> u32 {
         self.move_count.get()
     }
 
-    fn compute_move(&self, board: &Board, me: Player) -> Option<(u8, u8)> {
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let start = (self.clock)();
+        let move_count = self.move_count();
         let best_move = self.inner_compute_move(board, me, 1);
 
-        match best_move {
-            Some(move_found) => Some((move_found.x, move_found.y)),
-            None => None,
+        SearchResult {
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+            ..SearchResult::from_best_move(best_move, &self.evaluator, board, me, self.depth)
         }
     }
 }
 
 /// Implementation of the Alpha-Beta algorithm.
-pub struct AlphaBeta {
-    depth: u8,
+/// Positions reached through different move orders are searched once, thanks
+/// to a transposition table.
+///
+/// With a time limit, the search deepens iteratively up to the maximum
+/// depth, and the best move of the last completed iteration is returned when
+/// the time budget expires.
+///
+/// With an endgame solver, positions with few empty squares are solved
+/// exactly instead.
+pub struct AlphaBeta<E: Evaluator = WeightedEvaluator> {
+    // depth of the current search.
+    depth: Cell<u8>,
+    max_depth: u8,
     move_count: Cell<u32>,
+    table: RefCell<TranspositionTable>,
+    time_limit: Option<TimeLimit>,
+    deadline: Cell<Option<Deadline>>,
+    nodes_before_clock_check: Cell<u32>,
+    aborted: Cell<bool>,
+    depth_reached: Cell<u8>,
+    move_ordering: bool,
+    // best move of the previous iteration, explored first.
+    previous_best_move: Cell<Option<(u8, u8)>>,
+    // positions with at most this count of empty squares are solved.
+    endgame_empties: u8,
+    solver: EndgameSolver,
+    evaluator: E,
+    clock: Clock,
 }
 
 impl AlphaBeta {
     /// Creates a new AlphaBeta with, fixing its exploration depth.
     pub fn new(depth: u8) -> Self {
-        Self { depth, move_count: Cell::new(0) }
+        Self::with_evaluator(depth, WeightedEvaluator::default())
+    }
+}
+
+impl<E: Evaluator> AlphaBeta<E> {
+    /// Count of explored positions between two checks of the deadline.
+    const CLOCK_CHECK_INTERVAL: u32 = 64;
+
+    /// Creates a new AlphaBeta evaluating positions with the given evaluator.
+    pub fn with_evaluator(depth: u8, evaluator: E) -> Self {
+        Self {
+            depth: Cell::new(depth),
+            max_depth: depth,
+            move_count: Cell::new(0),
+            table: RefCell::new(TranspositionTable::new(TranspositionTable::DEFAULT_SIZE)),
+            time_limit: None,
+            deadline: Cell::new(None),
+            nodes_before_clock_check: Cell::new(0),
+            aborted: Cell::new(false),
+            depth_reached: Cell::new(0),
+            move_ordering: true,
+            previous_best_move: Cell::new(None),
+            endgame_empties: 0,
+            solver: EndgameSolver::new(),
+            evaluator,
+            clock: system_clock,
+        }
+    }
+
+    /// Sets the count of entries of the transposition table (0 to disable it).
+    pub fn with_table_size(mut self, table_size: usize) -> Self {
+        self.table = RefCell::new(TranspositionTable::new(table_size));
+        self
+    }
+
+    /// Sets a time budget for each move. The search deepens iteratively, the
+    /// depth given at creation becoming the maximum depth.
+    /// The search time is then measured with the clock of the time limit.
+    pub fn with_time_limit(mut self, time_limit: TimeLimit) -> Self {
+        self.time_limit = Some(time_limit);
+        self.clock = time_limit.clock();
+        self
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enables or disables move ordering (enabled by default). Exploring the
+    /// most promising moves first gives more cuts, without changing the move
+    /// found.
+    pub fn with_move_ordering(mut self, move_ordering: bool) -> Self {
+        self.move_ordering = move_ordering;
+        self
+    }
+
+    /// Solves exactly the positions having at most the given count of empty
+    /// squares (0 to disable it, the default). Above 16 the solver could be
+    /// slow.
+    pub fn with_endgame_solver(mut self, empties: u8) -> Self {
+        self.endgame_empties = empties;
+        self
+    }
+
+    /// Returns the count of positions found in the transposition table.
+    pub fn transposition_hits(&self) -> u32 {
+        self.table.borrow().hits()
+    }
+
+    /// Returns the depth of the last completed search.
+    pub fn depth_reached(&self) -> u8 {
+        self.depth_reached.get()
+    }
+
+    /// Deepens the search iteratively, until the maximum depth is reached
+    /// or the time budget expires.
+    fn iterative_deepening(
+        &self,
+        board: &Board,
+        me: Player,
+        deadline: Deadline,
+    ) -> Option<BestMove> {
+        self.deadline.set(Some(deadline));
+        let mut best_move = None;
+        for depth in 1..=self.max_depth {
+            self.depth.set(depth);
+            let iteration_best_move = self.inner_compute_move(board, me, 1, i32::MIN, i32::MAX);
+            if self.aborted.get() {
+                break;
+            }
+            best_move = iteration_best_move;
+            self.previous_best_move
+                .set(best_move.as_ref().map(|best| (best.x, best.y)));
+            self.depth_reached.set(depth);
+            if deadline.expired() {
+                break;
+            }
+        }
+        self.deadline.set(None);
+
+        best_move
+    }
+
+    /// Checks, from time to time, if the time budget is spent. The first
+    /// iteration is never aborted, to always find a move.
+    fn out_of_time(&self) -> bool {
+        if self.aborted.get() {
+            return true;
+        }
+        let deadline = match self.deadline.get() {
+            Some(deadline) if self.depth.get() > 1 => deadline,
+            _ => return false,
+        };
+
+        let nodes = self.nodes_before_clock_check.get();
+        if nodes > 0 {
+            self.nodes_before_clock_check.set(nodes - 1);
+            return false;
+        }
+        self.nodes_before_clock_check
+            .set(Self::CLOCK_CHECK_INTERVAL);
+        self.aborted.set(deadline.expired());
+        self.aborted.get()
+    }
+
+    /// Returns the evaluation stored in a transposition table entry, if it
+    /// allows to skip the search.
+    /// Only results of searches of the same depth are used : deeper ones
+    /// would be better, but the results would differ from a fixed depth
+    /// search.
+    fn known_evaluation(
+        &self,
+        entry: &TranspositionEntry,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<BestMove> {
+        if entry.depth != self.depth.get() - depth + 1 {
+            return None;
+        }
+
+        let usable = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => entry.evaluation >= beta,
+            Bound::Upper => entry.evaluation <= alpha,
+        };
+        if usable {
+            let (x, y) = entry.best_move;
+            Some(BestMove::new(x, y, entry.evaluation))
+        } else {
+            None
+        }
     }
 
-    /// Alpha-Beta implementation.
+    /// Alpha-Beta implementation, using the transposition table.
+    /// Returns None if the search is aborted.
     fn inner_compute_move(
         &self,
         board: &Board,
         current_player: Player,
         depth: u8,
         alpha: i32,
-        beta: i32
+        beta: i32,
+    ) -> Option<BestMove> {
+        if self.out_of_time() {
+            return None;
+        }
 
+        let key = board.zobrist_hash(current_player);
+        let entry = self.table.borrow_mut().probe(key);
+        let mut preferred_move = entry.map(|entry| entry.best_move);
+        if depth == 1 {
+            // The root position is always explored as a move is expected,
+            // starting with the best move of the previous iteration.
+            preferred_move = self.previous_best_move.get().or(preferred_move);
+        } else if let Some(known_move) = entry
+            .as_ref()
+            .and_then(|entry| self.known_evaluation(entry, depth, alpha, beta))
+        {
+            return Some(known_move);
+        }
+
+        let best_move =
+            self.search_moves(board, current_player, depth, alpha, beta, preferred_move);
+        if self.aborted.get() {
+            return None;
+        }
+
+        if let Some(BestMove {
+            x, y, evaluation, ..
+        }) = best_move
+        {
+            self.table.borrow_mut().store(TranspositionEntry {
+                key,
+                depth: self.depth.get() - depth + 1,
+                bound: Bound::for_window(evaluation, alpha, beta),
+                evaluation,
+                best_move: (x, y),
+            });
+        }
+
+        best_move
+    }
+
+    /// Returns the moves to explore. They're ordered to get cuts as soon as
+    /// possible, except at the maximum depth where all moves are evaluated.
+    fn moves_to_explore(
+        &self,
+        board: &Board,
+        current_player: Player,
+        depth: u8,
+        preferred_move: Option<(u8, u8)>,
+    ) -> Vec<(u8, u8)> {
+        if self.move_ordering && depth < self.depth.get() {
+            move_ordering::order_moves(board, current_player, preferred_move)
+        } else {
+            board.legal_moves(current_player).collect()
+        }
+    }
+
+    /// Explores the moves of a position.
+    fn search_moves(
+        &self,
+        board: &Board,
+        current_player: Player,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+        preferred_move: Option<(u8, u8)>,
     ) -> Option<BestMove> {
-        let mut best_move = None;
+        let mut best_move: Option<BestMove> = None;
         let mut current_alpha = alpha;
         let mut current_beta = beta;
-        for (x, y) in GridIterator::new() {
-            let opt_board_after_move = board
-                .play(current_player, x, y)
-                .expect("Unexpected error while computing move.");
+        for (x, y) in self.moves_to_explore(board, current_player, depth, preferred_move) {
+            // At the root, among moves of equal evaluation the first one in
+            // grid order is chosen, whatever the exploration order. Moves
+            // preceding the best one are then explored with a window
+            // allowing to detect an equal evaluation.
+            let root_tie_break = depth == 1
+                && best_move
+                    .as_ref()
+                    .is_some_and(|best| (y, x) < (best.y, best.x));
+            let (child_alpha, child_beta) = match (root_tie_break, current_player) {
+                (false, _) => (current_alpha, current_beta),
+                (true, Player::Black) => (current_alpha.saturating_sub(1), current_beta),
+                (true, Player::White) => (current_alpha, current_beta.saturating_add(1)),
+            };
 
-            // is the move valid ?
-            if let Some(board_after_move) = opt_board_after_move {
-                self.move_count.set(self.move_count() + 1);
-                if depth == self.depth {
-                    // max depth, just evaluate and returns
-                    let evaluation = Evaluator::evaluate(&board_after_move, current_player);
-                    best_move = BestMove::best_move_for_player(
-                        current_player,
-                        best_move,
-                        Some(BestMove { x, y, evaluation }),
-                    );
-                    continue;
-                }
+            // None is returned if the search is aborted.
+            let candidate = self.explore_move(
+                board,
+                current_player,
+                (x, y),
+                depth,
+                child_alpha,
+                child_beta,
+            )?;
 
-                // determine the next player, and check if the game is blocked.
-                let next_player = if board_after_move.can_player_move(current_player.opponent()) {
-                    // the player changes.
-                    current_player.opponent()
-                } else {
-                    if board_after_move.can_player_move(current_player) {
-                        // the game is not blocked, but the player does not change.
-                        current_player
-                    } else {
-                        // the game is blocked.
-                        let evaluation = Evaluator::evaluate(&board_after_move, current_player);
-                        best_move = BestMove::best_move_for_player(
-                            current_player,
-                            best_move,
-                            Some(BestMove { x, y, evaluation }),
-                        );
-                        continue;
-                    }
-                };
+            best_move = if root_tie_break
+                && best_move.as_ref().map(|best| best.evaluation) == Some(candidate.evaluation)
+            {
+                Some(candidate)
+            } else {
+                BestMove::best_move_for_player(current_player, best_move, Some(candidate))
+            };
 
-                let inner_best_move = self
-                    .inner_compute_move(&board_after_move, next_player, depth + 1, current_alpha, current_beta)
-                    .unwrap();
-                let BestMove {
-                    x: _,
-                    y: _,
-                    evaluation,
-                } = inner_best_move;
-                best_move = BestMove::best_move_for_player(
-                    current_player,
-                    best_move,
-                    Some(BestMove { x, y, evaluation }),
-                );
-                let best_eval = best_move.as_ref().unwrap().evaluation;
-                if current_player == Player::Black {
-                    if best_eval >= beta {
-                        // beta cut
-                        return best_move;
-                    }
-                    current_alpha = cmp::max(current_alpha, best_eval);
-                } else {
-                    if best_eval <= alpha {
-                        // alpha cut
-                        return best_move;
-                    }
-                    current_beta = cmp::min(current_beta, best_eval);
+            // No cut at the root, where a winning move reaches the window
+            // bounds : a move preceding it could win too.
+            let root = depth == 1;
+            let best_eval = best_move.as_ref().unwrap().evaluation;
+            if current_player == Player::Black {
+                if !root && best_eval >= beta {
+                    // beta cut
+                    return best_move;
                 }
+                current_alpha = cmp::max(current_alpha, best_eval);
+            } else {
+                if !root && best_eval <= alpha {
+                    // alpha cut
+                    return best_move;
+                }
+                current_beta = cmp::min(current_beta, best_eval);
             }
         }
 
-        return best_move;
+        best_move
+    }
+
+    /// Plays a move and evaluates it, exploring deeper if the maximum depth
+    /// isn't reached. Returns None if the search is aborted.
+    fn explore_move(
+        &self,
+        board: &Board,
+        current_player: Player,
+        (x, y): (u8, u8),
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> Option<BestMove> {
+        let board_after_move = board
+            .play(current_player, x, y)
+            .expect("Unexpected error while computing move.")
+            .expect("Unexpected invalid move while computing move.");
+        self.move_count.set(self.move_count() + 1);
+
+        if depth == self.depth.get() {
+            // max depth, just evaluate.
+            let evaluation = self.evaluator.evaluate(&board_after_move, current_player);
+            Some(BestMove::new(x, y, evaluation))
+        } else if let Some(next_player) = next_player(&board_after_move, current_player) {
+            let inner_best_move =
+                self.inner_compute_move(&board_after_move, next_player, depth + 1, alpha, beta)?;
+            Some(BestMove::followed_by(x, y, inner_best_move))
+        } else {
+            // the game is blocked.
+            let evaluation = self.evaluator.evaluate(&board_after_move, current_player);
+            Some(BestMove::new(x, y, evaluation))
+        }
     }
 }
 
-impl VirtualPlayer for AlphaBeta {
+impl<E: Evaluator> VirtualPlayer for AlphaBeta<E> {
     fn move_count(&self) -> u32 {
         self.move_count.get()
     }
 
-    fn compute_move(&self, board: &Board, me: Player) -> Option<(u8, u8)> {
-        let best_move = self.inner_compute_move(board, me, 1, i32::MIN,i32::MAX);
-
-        match best_move {
-            Some(move_found) => Some((move_found.x, move_found.y)),
-            None => None,
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let start = (self.clock)();
+        let move_count = self.move_count();
+        let result = self.inner_search(board, me);
+        SearchResult {
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+            ..result
         }
     }
 }
 
-/// Evaluator is responsible for the evaluation of the state of a game.
-/// No instance is needed, all methods are statics. Evaluator could become
-/// configurable later, but now it's rather a naive implementation.
-struct Evaluator;
-
-impl Evaluator {
-    // game is over and there is a winner.
-    const SCORE_MAX: i32 = i32::MAX;
-    // game over and no winner.
-    const SCORE_DRAW: i32 = 0;
-    // bonus if the opponent can't move the next turn.
-    const SCORE_OPPONENT_BLOCKED: i32 = 4;
-
-    // Scores according to piece position.
-    const SCORE_INSIDE: i32 = 1;
-    const SCORE_BORDER: i32 = 4;
-    const SCORE_CORNER: i32 = 8;
-
-    /// Returns an evaluation for the given board, when the last move was done
-    /// by the given player.
-    /// If the evaluation is ...
-    /// * positive : Black player is stronger.
-    /// * negative : White player is stronger.
-    fn evaluate(board: &Board, last_player: Player) -> i32 {
-        let status = GameStatus::evaluate_board(board);
-        if status.game_over() {
-            return match status.winner() {
-                Some(winner) => Self::sign_for_player(winner, Self::SCORE_MAX),
-                None => Self::SCORE_DRAW,
+impl<E: Evaluator> AlphaBeta<E> {
+    /// Searches the best move, solving the endgames or using alpha-beta.
+    fn inner_search(&self, board: &Board, me: Player) -> SearchResult {
+        let (black_pieces, white_pieces) = board.count_pieces();
+        let empties = 64 - black_pieces - white_pieces;
+        if self.endgame_empties > 0 && empties <= self.endgame_empties {
+            let solver_move_count = self.solver.move_count();
+            let solution = self.solver.solve(board, me);
+            self.move_count
+                .set(self.move_count() + self.solver.move_count() - solver_move_count);
+            self.depth_reached.set(empties);
+            return SearchResult {
+                best_move: solution.best_move,
+                exact: true,
+                principal_variation: solution.principal_variation,
+                ..SearchResult::without_move(empties, solution.score as i32)
             };
         }
 
-        let mut corner = 0;
-        let mut border = 0;
-        let mut other = 0;
-        for (x, y, piece) in board.iter() {
-            if let Some(player) = piece {
-                if Self::corner(x, y) {
-                    corner += Self::sign_for_player(player, Self::SCORE_CORNER);
-                } else if Self::border(x, y) {
-                    border += Self::sign_for_player(player, Self::SCORE_BORDER);
-                } else {
-                    other += Self::sign_for_player(player, Self::SCORE_INSIDE);
-                }
+        self.table.borrow_mut().new_search();
+        self.aborted.set(false);
+        self.previous_best_move.set(None);
+        let best_move = match self.time_limit {
+            None => {
+                let best_move = self.inner_compute_move(board, me, 1, i32::MIN, i32::MAX);
+                self.depth_reached.set(self.max_depth);
+                best_move
             }
+            Some(time_limit) => self.iterative_deepening(board, me, time_limit.start()),
+        };
+
+        SearchResult::from_best_move(best_move, &self.evaluator, board, me, self.depth_reached())
+    }
+}
+
+/// Parallel implementation of the Alpha-Beta algorithm, splitting the root :
+/// the moves of the position are shared between threads, each one exploring
+/// them with its own AlphaBeta (and its own transposition table). The best
+/// evaluation found is shared, allowing the threads to cut the other moves.
+///
+/// The move found is the same as the one of AlphaBeta for the same depth,
+/// whatever the count of threads.
+pub struct ParallelAlphaBeta<E: Evaluator = WeightedEvaluator> {
+    depth: u8,
+    workers: Vec<Mutex<AlphaBeta<E>>>,
+    table_size: usize,
+    endgame_empties: u8,
+    evaluator: E,
+    clock: Clock,
+}
+
+impl ParallelAlphaBeta {
+    /// Creates a new ParallelAlphaBeta, fixing its exploration depth. It uses
+    /// one thread per available core.
+    pub fn new(depth: u8) -> Self {
+        Self::with_evaluator(depth, WeightedEvaluator::default())
+    }
+}
+
+impl<E: Evaluator + Clone + Send> ParallelAlphaBeta<E> {
+    /// Creates a new ParallelAlphaBeta evaluating positions with the given
+    /// evaluator.
+    pub fn with_evaluator(depth: u8, evaluator: E) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        Self {
+            depth,
+            workers: Vec::new(),
+            table_size: TranspositionTable::DEFAULT_SIZE,
+            endgame_empties: 0,
+            evaluator,
+            clock: system_clock,
         }
+        .with_threads(threads)
+    }
+
+    /// Sets the count of threads (at least one).
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.workers = (0..threads.max(1))
+            .map(|_| Mutex::new(self.worker()))
+            .collect();
+        self
+    }
+
+    /// Sets the count of entries of the transposition table of each thread
+    /// (0 to disable them).
+    pub fn with_table_size(mut self, table_size: usize) -> Self {
+        self.table_size = table_size;
+        let threads = self.threads();
+        self.with_threads(threads)
+    }
+
+    /// Sets the clock used to measure the search time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        let threads = self.threads();
+        self.with_threads(threads)
+    }
+
+    /// Solves exactly the positions having at most the given count of empty
+    /// squares (0 to disable it, the default). The solver uses a single
+    /// thread.
+    pub fn with_endgame_solver(mut self, empties: u8) -> Self {
+        self.endgame_empties = empties;
+        let threads = self.threads();
+        self.with_threads(threads)
+    }
 
-        let mut evaluation = corner + border + other;
+    /// Returns the count of threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
 
-        if !status.can_player_move(last_player.opponent()) {
-            evaluation += Self::sign_for_player(last_player, Self::SCORE_OPPONENT_BLOCKED);
+    /// Creates the AlphaBeta used by a thread.
+    fn worker(&self) -> AlphaBeta<E> {
+        AlphaBeta::with_evaluator(self.depth, self.evaluator.clone())
+            .with_table_size(self.table_size)
+            .with_clock(self.clock)
+            .with_endgame_solver(self.endgame_empties)
+    }
+
+    /// Explores the moves of the root position, each thread taking the next
+    /// move not explored yet.
+    fn search_root(&self, board: &Board, me: Player) -> Option<BestMove> {
+        let moves = move_ordering::order_moves(board, me, None);
+        let (&first_move, other_moves) = moves.split_first()?;
+        for worker in self.workers.iter() {
+            worker.lock().unwrap().table.borrow_mut().new_search();
         }
 
-        evaluation
+        // The first move, the most promising one, is explored before the
+        // others to share its evaluation as soon as possible.
+        let first_best_move = self.workers[0]
+            .lock()
+            .unwrap()
+            .explore_move(board, me, first_move, 1, i32::MIN, i32::MAX)
+            .expect("Unexpected aborted search.");
+        let best_move = Mutex::new(Some(first_best_move));
+        let next_move = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for worker in self.workers.iter() {
+                let (moves, next_move, best_move) = (other_moves, &next_move, &best_move);
+                scope.spawn(move || {
+                    let worker = worker.lock().unwrap();
+                    while let Some(&(x, y)) = moves.get(next_move.fetch_add(1, Ordering::Relaxed)) {
+                        let (alpha, beta) = Self::window(best_move.lock().unwrap().as_ref(), me);
+                        let candidate = worker
+                            .explore_move(board, me, (x, y), 1, alpha, beta)
+                            .expect("Unexpected aborted search.");
+                        let mut best_move = best_move.lock().unwrap();
+                        if Self::better(&candidate, best_move.as_ref(), me) {
+                            *best_move = Some(candidate);
+                        }
+                    }
+                });
+            }
+        });
+
+        best_move.into_inner().unwrap()
+    }
+
+    /// Returns the window to explore a move, knowing the best move found so
+    /// far. An evaluation equal to the best one must be detected, as the
+    /// first move in grid order is chosen among moves of equal evaluation.
+    fn window(best_move: Option<&BestMove>, me: Player) -> (i32, i32) {
+        match (best_move, me) {
+            (None, _) => (i32::MIN, i32::MAX),
+            (Some(best), Player::Black) => (best.evaluation.saturating_sub(1), i32::MAX),
+            (Some(best), Player::White) => (i32::MIN, best.evaluation.saturating_add(1)),
+        }
     }
 
-    /// Change the sign if the given evaluation (or intermediate one) if the
-    /// player is White.
-    fn sign_for_player(player: Player, evaluation: i32) -> i32 {
-        match player {
-            Player::Black => evaluation,
-            Player::White => -evaluation,
+    /// Tells if a move is better than the best one found so far. Moves being
+    /// explored in parallel, a move could be found after a move following it
+    /// in grid order.
+    fn better(candidate: &BestMove, best_move: Option<&BestMove>, me: Player) -> bool {
+        match best_move {
+            None => true,
+            Some(best) => {
+                let candidate_evaluation = candidate.normalized_evaluation(me);
+                let best_evaluation = best.normalized_evaluation(me);
+                candidate_evaluation > best_evaluation
+                    || (candidate_evaluation == best_evaluation
+                        && (candidate.y, candidate.x) < (best.y, best.x))
+            }
         }
     }
+}
 
-    fn corner(x: u8, y: u8) -> bool {
-        (x == 0 || x == 7) && (y == 0 || y == 7)
+impl<E: Evaluator + Clone + Send> VirtualPlayer for ParallelAlphaBeta<E> {
+    fn move_count(&self) -> u32 {
+        self.workers
+            .iter()
+            .map(|worker| worker.lock().unwrap().move_count())
+            .sum()
     }
 
-    fn border(x: u8, y: u8) -> bool {
-        x == 0 || x == 7 || y == 0 || y == 7
+    fn search(&self, board: &Board, me: Player) -> SearchResult {
+        let (black_pieces, white_pieces) = board.count_pieces();
+        if self.endgame_empties > 0 && 64 - black_pieces - white_pieces <= self.endgame_empties {
+            return self.workers[0].lock().unwrap().search(board, me);
+        }
+
+        let start = (self.clock)();
+        let move_count = self.move_count();
+        let best_move = self.search_root(board, me);
+        SearchResult {
+            nodes: self.move_count() - move_count,
+            elapsed: (self.clock)().saturating_sub(start),
+            ..SearchResult::from_best_move(best_move, &self.evaluator, board, me, self.depth)
+        }
     }
 }
 
@@ -298,9 +863,40 @@ struct BestMove {
     x: u8,
     y: u8,
     evaluation: i32,
+    // the expected moves after this one.
+    continuation: Vec<(u8, u8)>,
 }
 
 impl BestMove {
+    /// Creates a move evaluated without exploring further.
+    fn new(x: u8, y: u8, evaluation: i32) -> Self {
+        Self {
+            x,
+            y,
+            evaluation,
+            continuation: Vec::new(),
+        }
+    }
+
+    /// Creates a move whose evaluation comes from the best move of the next
+    /// turn.
+    fn followed_by(x: u8, y: u8, next: BestMove) -> Self {
+        Self {
+            x,
+            y,
+            evaluation: next.evaluation,
+            continuation: next.line(),
+        }
+    }
+
+    /// Returns the move followed by the expected continuation.
+    fn line(self) -> Vec<(u8, u8)> {
+        let mut line = Vec::with_capacity(self.continuation.len() + 1);
+        line.push((self.x, self.y));
+        line.extend(self.continuation);
+        line
+    }
+
     /// Choose the best move between the two given, for the given player.
     fn best_move_for_player(
         current_player: Player,
@@ -314,40 +910,32 @@ impl BestMove {
             return move_a;
         }
 
-        let eval_a = move_a.as_ref().unwrap().normalized_evaluation(current_player);
-        let eval_b = move_b.as_ref().unwrap().normalized_evaluation(current_player);
-        return if eval_a >= eval_b { move_a } else { move_b };
+        let eval_a = move_a
+            .as_ref()
+            .unwrap()
+            .normalized_evaluation(current_player);
+        let eval_b = move_b
+            .as_ref()
+            .unwrap()
+            .normalized_evaluation(current_player);
+        if eval_a >= eval_b {
+            move_a
+        } else {
+            move_b
+        }
     }
 
     /// Returns an evaluation, normalized to be 'greater is better' for the player.
     fn normalized_evaluation(&self, player: Player) -> i32 {
-        Evaluator::sign_for_player(player, self.evaluation)
+        normalize(self.evaluation, player)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::super::Game;
     use super::*;
-
-    #[test]
-    fn evaluate_returns_zero_for_equals_forces() {
-        let board = Board::new_start();
-        assert_eq!(0, Evaluator::evaluate(&board, Player::Black));
-    }
-
-    #[test]
-    fn evaluate_returns_positive_score_if_black_is_stronger() {
-        let board = Board::new_start();
-        let board = board.play(Player::Black, 4, 5).unwrap().unwrap();
-        assert!(Evaluator::evaluate(&board, Player::Black) > 0);
-    }
-
-    #[test]
-    fn evaluate_returns_negative_score_if_white_is_stronger() {
-        let mut board = Board::new_start();
-        board.set_piece(3, 4, Some(Player::White)).unwrap();
-        assert!(Evaluator::evaluate(&board, Player::Black) < 0);
-    }
+    use std::time::Duration;
 
     #[test]
     fn minimax_find_a_move() {
@@ -359,12 +947,17 @@ mod test {
 
     #[test]
     fn minimax_find_the_best_move() {
-        let mut board = Board::new();
-        board.set_piece(2, 2, Some(Player::White)).unwrap();
-        board.set_piece(3, 2, Some(Player::Black)).unwrap();
-        board.set_piece(2, 3, Some(Player::White)).unwrap();
-        board.set_piece(3, 3, Some(Player::Black)).unwrap();
-        board.set_piece(4, 3, Some(Player::Black)).unwrap();
+        let board = Board::from_position(
+            "--------\
+             --------\
+             --OX----\
+             --OXX---\
+             --------\
+             --------\
+             --------\
+             --------",
+        )
+        .unwrap();
         let minimax = Minimax::new(1);
         let best_move = minimax.compute_move(&board, Player::White);
         assert_eq!(best_move, Some((5, 3)));
@@ -380,24 +973,319 @@ mod test {
 
     #[test]
     fn alphabeta_find_the_best_move() {
-        let mut board = Board::new();
-        board.set_piece(2, 2, Some(Player::White)).unwrap();
-        board.set_piece(3, 2, Some(Player::Black)).unwrap();
-        board.set_piece(2, 3, Some(Player::White)).unwrap();
-        board.set_piece(3, 3, Some(Player::Black)).unwrap();
-        board.set_piece(4, 3, Some(Player::Black)).unwrap();
+        let board = Board::from_position(
+            "--------\
+             --------\
+             --OX----\
+             --OXX---\
+             --------\
+             --------\
+             --------\
+             --------",
+        )
+        .unwrap();
         let alphabeta = AlphaBeta::new(1);
         let best_move = alphabeta.compute_move(&board, Player::White);
         assert_eq!(best_move, Some((5, 3)));
     }
 
+    /// Plays the given moves from the given position, passes being implicit.
+    fn play_variation(board: &Board, player: Player, variation: &[(u8, u8)]) -> Game {
+        let mut game = Game::from_board(*board, player);
+        for &(x, y) in variation {
+            game.play(game.player().unwrap(), x, y).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn minimax_search_reports_the_principal_variation() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let minimax = Minimax::new(3);
+        let result = minimax.search(game.board(), player);
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.nodes, minimax.move_count());
+        assert_eq!(result.principal_variation.len(), 3);
+        assert_eq!(result.best_move, Some(result.principal_variation[0]));
+
+        // The score is the evaluation of the position at the end of the
+        // principal variation (evaluations are from the Black point of view).
+        assert_eq!(player, Player::Black);
+        let end = play_variation(game.board(), player, &result.principal_variation);
+        let last_player = end.history().last().unwrap().player();
+        let evaluation = WeightedEvaluator::default().evaluate(end.board(), last_player);
+        assert_eq!(result.score, evaluation);
+    }
+
+    #[test]
+    fn alphabeta_search_reports_the_same_score_as_minimax() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let minimax = Minimax::new(4).search(game.board(), player);
+        let alphabeta = AlphaBeta::new(4);
+        let result = alphabeta.search(game.board(), player);
+        assert_eq!(result.best_move, minimax.best_move);
+        assert_eq!(result.score, minimax.score);
+        assert_eq!(result.depth, 4);
+        assert_eq!(result.nodes, alphabeta.move_count());
+        assert!(!result.principal_variation.is_empty());
+        assert!(result.principal_variation.len() <= 4);
+        assert_eq!(result.best_move, Some(result.principal_variation[0]));
+        play_variation(game.board(), player, &result.principal_variation);
+    }
+
+    #[test]
+    fn analyze_scores_all_moves_best_first() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let results = Minimax::new(2).analyze(game.board(), player);
+        assert_eq!(results.len(), game.board().mobility(player) as usize);
+        assert!(results
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+        for result in results.iter() {
+            assert_eq!(result.depth, 3);
+            assert_eq!(result.best_move, Some(result.principal_variation[0]));
+        }
+
+        // The best move is the one found by a search of the same depth.
+        let search = Minimax::new(3).search(game.board(), player);
+        assert_eq!(results[0].best_move, search.best_move);
+        assert_eq!(results[0].score, search.score);
+        let alphabeta_results = AlphaBeta::new(2).analyze(game.board(), player);
+        let scores = |results: &[SearchResult]| -> Vec<i32> {
+            results.iter().map(|result| result.score).collect()
+        };
+        assert_eq!(scores(&alphabeta_results), scores(&results));
+    }
+
+    #[test]
+    fn analyze_manages_game_over() {
+        let board = Board::from_position(&format!("-O{}", "X".repeat(62))).unwrap();
+        let results = AlphaBeta::new(4).analyze(&board, Player::Black);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].score, i32::MAX);
+        assert_eq!(results[0].principal_variation, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn search_without_move_reports_nothing() {
+        let board = Board::from_position(&"X".repeat(64)).unwrap();
+        let result = AlphaBeta::new(4).search(&board, Player::White);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, -i32::MAX);
+        assert!(result.principal_variation.is_empty());
+        assert_eq!(result.nodes, 0);
+    }
+
+    /// An evaluator wanting as few pieces as possible.
+    struct FewestPieces;
+
+    impl Evaluator for FewestPieces {
+        fn evaluate(&self, board: &Board, _last_player: Player) -> i32 {
+            let (black_pieces, white_pieces) = board.count_pieces();
+            white_pieces as i32 - black_pieces as i32
+        }
+    }
+
+    #[test]
+    fn virtual_players_use_the_given_evaluator() {
+        // c3, d3, e3 and f3 flip one piece, when c4 flips two.
+        let board = Board::from_position(
+            "--------\
+             --------\
+             --------\
+             ---OOX--\
+             ---XX---\
+             --------\
+             --------\
+             --------",
+        )
+        .unwrap();
+        let minimax = Minimax::with_evaluator(1, FewestPieces);
+        assert_eq!(minimax.compute_move(&board, Player::Black), Some((2, 2)));
+        let alphabeta = AlphaBeta::with_evaluator(1, FewestPieces);
+        assert_eq!(alphabeta.compute_move(&board, Player::Black), Some((2, 2)));
+        let alphabeta = AlphaBeta::new(1);
+        assert_eq!(alphabeta.compute_move(&board, Player::Black), Some((2, 3)));
+    }
+
+    #[test]
+    fn alphabeta_behave_the_same_as_minimax_with_another_evaluator() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let minimax = Minimax::with_evaluator(4, WeightedEvaluator::classic());
+        let alphabeta = AlphaBeta::with_evaluator(4, WeightedEvaluator::classic());
+        assert_eq!(
+            minimax.compute_move(game.board(), player),
+            alphabeta.compute_move(game.board(), player)
+        );
+    }
+
+    #[test]
+    fn alphabeta_transposition_table_saves_moves_without_changing_result() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let with_table = AlphaBeta::new(6);
+        let without_table = AlphaBeta::new(6).with_table_size(0);
+        assert_eq!(
+            with_table.compute_move(game.board(), player),
+            without_table.compute_move(game.board(), player)
+        );
+        assert!(with_table.transposition_hits() > 0);
+        assert_eq!(without_table.transposition_hits(), 0);
+        assert!(with_table.move_count() < without_table.move_count());
+    }
+
+    #[test]
+    fn alphabeta_move_ordering_saves_moves_without_changing_result() {
+        let positions = [
+            "f5d6c3d3c4f4f6f3e6e7",
+            "f5f6e6f4e3c5c4e7",
+            "c4e3f6e6f5c5f4g6f7",
+        ];
+        let mut moves_with_ordering = 0;
+        let mut moves_without_ordering = 0;
+        for transcript in positions.iter() {
+            let game = Game::from_transcript(transcript).unwrap();
+            let player = game.player().unwrap();
+            let with_ordering = AlphaBeta::new(6);
+            let without_ordering = AlphaBeta::new(6).with_move_ordering(false);
+            assert_eq!(
+                with_ordering.compute_move(game.board(), player),
+                without_ordering.compute_move(game.board(), player)
+            );
+            moves_with_ordering += with_ordering.move_count();
+            moves_without_ordering += without_ordering.move_count();
+        }
+        assert!(moves_with_ordering < moves_without_ordering);
+    }
+
+    #[test]
+    fn alphabeta_with_endgame_solver_plays_perfectly() {
+        let game = Game::from_position(
+            "OOOOXXXO X-OXOXXX X-XOXOXX XXOXOOXX XXXXXOXO \
+             OOOOOOO- ---XOOOO ---X-OOO X",
+        )
+        .unwrap();
+        let alphabeta = AlphaBeta::new(2).with_endgame_solver(10);
+        let result = alphabeta.search(game.board(), Player::Black);
+        assert_eq!(alphabeta.depth_reached(), 10);
+        assert!(alphabeta.move_count() > 0);
+        let solution = EndgameSolver::new().solve(game.board(), Player::Black);
+        assert_eq!(result.best_move, solution.best_move);
+        assert_eq!(result.score, solution.score as i32);
+        assert!(result.exact);
+        assert!(alphabeta
+            .analyze(game.board(), Player::Black)
+            .iter()
+            .all(|result| result.exact));
+        let alphabeta = AlphaBeta::new(2).with_endgame_solver(9);
+        assert!(!alphabeta.search(game.board(), Player::Black).exact);
+        assert_eq!(alphabeta.depth_reached(), 2);
+    }
+
+    fn frozen_clock() -> Duration {
+        Duration::from_secs(0)
+    }
+
+    #[test]
+    fn alphabeta_with_time_limit_deepens_up_to_max_depth() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let time_limit = TimeLimit::with_clock(Duration::from_secs(1), frozen_clock);
+        let alphabeta = AlphaBeta::new(5).with_time_limit(time_limit);
+        let best_move = alphabeta.compute_move(game.board(), player);
+        assert_eq!(alphabeta.depth_reached(), 5);
+        assert_eq!(
+            best_move,
+            AlphaBeta::new(5).compute_move(game.board(), player)
+        );
+    }
+
+    #[test]
+    fn alphabeta_with_time_limit_returns_the_last_completed_iteration() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let time_limit = TimeLimit::with_clock(Duration::from_secs(0), frozen_clock);
+        let alphabeta = AlphaBeta::new(8).with_time_limit(time_limit);
+        let best_move = alphabeta.compute_move(game.board(), player);
+        assert_eq!(alphabeta.depth_reached(), 1);
+        assert_eq!(
+            best_move,
+            AlphaBeta::new(1).compute_move(game.board(), player)
+        );
+    }
+
+    #[test]
+    fn alphabeta_with_time_limit_stops_when_the_budget_expires() {
+        let board = Board::new_start();
+        let time_limit = TimeLimit::new(Duration::from_millis(50));
+        let alphabeta = AlphaBeta::new(60).with_time_limit(time_limit);
+        let start = std::time::Instant::now();
+        let best_move = alphabeta.compute_move(&board, Player::Black);
+        assert!(best_move.is_some());
+        assert!(alphabeta.depth_reached() < 60);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn parallel_alphabeta_finds_the_same_moves_as_alphabeta() {
+        let positions = [
+            "f5d6c3d3c4f4f6f3e6e7",
+            "f5f6e6f4e3c5c4e7",
+            "c4e3f6e6f5c5f4g6f7",
+        ];
+        let parallel = ParallelAlphaBeta::new(6).with_threads(4);
+        assert_eq!(parallel.threads(), 4);
+        for transcript in positions.iter() {
+            let game = Game::from_transcript(transcript).unwrap();
+            let player = game.player().unwrap();
+            let expected = AlphaBeta::new(6).search(game.board(), player);
+            let result = parallel.search(game.board(), player);
+            assert_eq!(result.best_move, expected.best_move);
+            assert_eq!(result.score, expected.score);
+            assert_eq!(result.best_move, Some(result.principal_variation[0]));
+        }
+    }
+
+    #[test]
+    fn parallel_alphabeta_single_threaded_is_deterministic() {
+        let game = Game::from_transcript("f5d6c3d3c4f4f6f3e6e7").unwrap();
+        let player = game.player().unwrap();
+        let first = ParallelAlphaBeta::new(6)
+            .with_threads(1)
+            .with_clock(frozen_clock)
+            .search(game.board(), player);
+        let second = ParallelAlphaBeta::new(6)
+            .with_threads(1)
+            .with_clock(frozen_clock)
+            .search(game.board(), player);
+        assert_eq!(first, second);
+        assert!(first.nodes > 0);
+    }
+
+    #[test]
+    fn parallel_alphabeta_with_endgame_solver_plays_perfectly() {
+        let game = Game::from_position(
+            "OOOOXXXO X-OXOXXX X-XOXOXX XXOXOOXX XXXXXOXO \
+             OOOOOOO- ---XOOOO ---X-OOO X",
+        )
+        .unwrap();
+        let parallel = ParallelAlphaBeta::new(2).with_endgame_solver(10);
+        let result = parallel.search(game.board(), Player::Black);
+        let solution = EndgameSolver::new().solve(game.board(), Player::Black);
+        assert_eq!(result.best_move, solution.best_move);
+        assert_eq!(result.score, solution.score as i32);
+    }
+
     /// This test take more time and is only done when the feature flag is activated.
     /// Disabling capture show each 'best' move found, and the move counts per
     /// algorithms.
-    #[cfg(feature="alphabetavsminimax")]
+    #[cfg(feature = "alphabetavsminimax")]
     #[test]
     fn alpha_beta_behave_the_same_as_minimax() {
-        use super::super::Game;
         let mut game = Game::new();
         let minimax = Minimax::new(4);
         let alpha_beta = AlphaBeta::new(4);
@@ -407,11 +1295,18 @@ mod test {
             let alphabeta_result = alpha_beta.compute_move(game.board(), game.player().unwrap());
             assert_eq!(minimax_result, alphabeta_result);
             // play the move... et continue the game
-            println!("Move : {:?} / move counts : minimax {} - {} alphabeta", alphabeta_result, minimax.move_count(), alpha_beta.move_count());
+            println!(
+                "Move : {:?} / move counts : minimax {} - {} alphabeta ({} transposition hits)",
+                alphabeta_result,
+                minimax.move_count(),
+                alpha_beta.move_count(),
+                alpha_beta.transposition_hits()
+            );
             match alphabeta_result {
-                Some((x,y)) => game.play(game.player().unwrap(), x, y),
+                Some((x, y)) => game.play(game.player().unwrap(), x, y),
                 None => panic!("Unexpected empty move."),
-            }.unwrap();
+            }
+            .unwrap();
         }
     }
 }
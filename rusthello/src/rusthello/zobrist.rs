@@ -0,0 +1,137 @@
+use super::board::*;
+use super::random::Random;
+
+/// Zobrist keys, one per cell and per player.
+const PIECES_KEYS: [[u64; 64]; 2] = generate_keys();
+/// Zobrist key added when White is the next player.
+const WHITE_TO_MOVE_KEY: u64 = 0x4f1b_bcdc_bfa5_3e0b;
+
+/// Generates Zobrist keys with a seeded generator, at compile time.
+const fn generate_keys() -> [[u64; 64]; 2] {
+    let mut keys = [[0; 64]; 2];
+    let mut random = Random::new(0x2545_f491_4f6c_dd1d);
+    let mut index = 0;
+    while index < 128 {
+        keys[index / 64][index % 64] = random.next_u64();
+        index += 1;
+    }
+    keys
+}
+
+/// Returns the Zobrist key of a piece of the given player on the bit
+/// `index` of the bitboards.
+pub fn piece_key(player: Player, index: u32) -> u64 {
+    PIECES_KEYS[player as usize][index as usize]
+}
+
+/// Returns the Zobrist key changing the owner of the piece on the bit
+/// `index` of the bitboards : it's the same for both players.
+pub fn flip_key(index: u32) -> u64 {
+    PIECES_KEYS[0][index as usize] ^ PIECES_KEYS[1][index as usize]
+}
+
+/// Returns the Zobrist key of the next player.
+pub fn player_key(player: Player) -> u64 {
+    match player {
+        Player::Black => 0,
+        Player::White => WHITE_TO_MOVE_KEY,
+    }
+}
+
+/// Computes from scratch the Zobrist hash of the pieces of both players,
+/// see `Board::zobrist_hash` for the incremental one.
+pub fn pieces_hash(black: u64, white: u64) -> u64 {
+    let mut hash = 0;
+    for (player, pieces) in [(Player::Black, black), (Player::White, white)].iter() {
+        let mut pieces = *pieces;
+        while pieces != 0 {
+            hash ^= piece_key(*player, pieces.trailing_zeros());
+            pieces &= pieces - 1;
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_all_differents() {
+        let mut keys: Vec<u64> = PIECES_KEYS.iter().flatten().copied().collect();
+        keys.push(WHITE_TO_MOVE_KEY);
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), 129);
+    }
+
+    #[test]
+    fn hash_depends_on_next_player() {
+        let board = Board::new_start();
+        assert_ne!(
+            board.zobrist_hash(Player::Black),
+            board.zobrist_hash(Player::White)
+        );
+    }
+
+    #[test]
+    fn hash_only_depends_on_the_position() {
+        let board = Board::new_start()
+            .play(Player::Black, 5, 4)
+            .unwrap()
+            .unwrap();
+        let same_board = Board::from_position(&board.to_position()).unwrap();
+        assert_eq!(
+            board.zobrist_hash(Player::White),
+            same_board.zobrist_hash(Player::White)
+        );
+        let other_board = Board::new_start()
+            .play(Player::Black, 4, 5)
+            .unwrap()
+            .unwrap();
+        assert_ne!(
+            board.zobrist_hash(Player::White),
+            other_board.zobrist_hash(Player::White)
+        );
+    }
+
+    #[test]
+    fn hash_of_empty_board_is_zero_for_black() {
+        assert_eq!(Board::new().zobrist_hash(Player::Black), 0);
+    }
+
+    #[test]
+    fn incremental_hash_matches_the_full_computation() {
+        let mut board = Board::new_start();
+        let mut player = Player::Black;
+        // Plays the last legal move until the end of the game.
+        loop {
+            if !board.can_player_move(player) {
+                player = player.opponent();
+                if !board.can_player_move(player) {
+                    break;
+                }
+            }
+            let (x, y) = board.legal_moves(player).last().unwrap();
+            board = board.play(player, x, y).unwrap().unwrap();
+            player = player.opponent();
+            let black = board.bitboard(Player::Black);
+            let white = board.bitboard(Player::White);
+            assert_eq!(
+                board.zobrist_hash(player),
+                pieces_hash(black, white) ^ player_key(player)
+            );
+        }
+    }
+
+    #[test]
+    fn set_piece_updates_the_hash() {
+        let mut board = Board::new_start();
+        board.set_piece(3, 3, Some(Player::Black)).unwrap();
+        board.set_piece(0, 0, Some(Player::White)).unwrap();
+        board.set_piece(0, 0, None).unwrap();
+        let black = board.bitboard(Player::Black);
+        let white = board.bitboard(Player::White);
+        assert_eq!(board.zobrist_hash(Player::Black), pieces_hash(black, white));
+    }
+}